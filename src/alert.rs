@@ -0,0 +1,116 @@
+use crate::datetime;
+use crate::todo::Todo;
+
+use chrono::{DateTime, TimeZone};
+
+use std::time::Duration;
+
+/// Limits a todo list is held to, like an SLO. Unset limits never breach.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Policy {
+    pub max_open: Option<usize>,
+    pub max_overdue: Option<usize>,
+    /// No open todo may be overdue for longer than this
+    pub max_overdue_age: Option<Duration>,
+}
+
+impl Policy {
+    pub fn is_empty(&self) -> bool {
+        *self == Policy::default()
+    }
+
+    /// Describes every limit the todos breach, or nothing if all hold
+    pub fn breaches<Tz>(&self, todos: &[Todo], now: &DateTime<Tz>) -> Vec<String>
+    where
+        Tz: TimeZone,
+    {
+        let open = todos.iter().filter(|todo| !todo.done).count();
+        let overdue = todos.iter().filter(|todo| todo.is_overdue(now)).count();
+
+        let mut breaches: Vec<_> = [
+            ("open", open, self.max_open),
+            ("overdue", overdue, self.max_overdue),
+        ]
+        .into_iter()
+        .filter_map(|(kind, count, max)| {
+            max.filter(|max| count > *max)
+                .map(|max| format!("{count} {kind} todos exceed the maximum of {max}"))
+        })
+        .collect();
+
+        if let Some(max_age) = self.max_overdue_age {
+            let tz = now.timezone();
+            for todo in todos.iter().filter(|todo| todo.is_overdue(now)) {
+                let age = todo
+                    .deadline(&tz)
+                    .and_then(|deadline| (now.clone() - deadline).to_std().ok());
+                if let Some(age) = age.filter(|age| *age > max_age) {
+                    breaches.push(format!(
+                        "'{}' is overdue by {}, more than {}",
+                        todo.title,
+                        datetime::format_duration(age),
+                        datetime::format_duration(max_age)
+                    ));
+                }
+            }
+        }
+
+        breaches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn now() -> DateTime<Utc> {
+        "2024-05-03T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_empty_policy_never_breaches() {
+        let todos = [Todo::sample("Late", false, Some("2024-01-01"))];
+
+        assert!(Policy::default().is_empty());
+        assert!(Policy::default().breaches(&todos, &now()).is_empty());
+    }
+
+    #[test]
+    fn test_count_limits() {
+        let todos = [
+            Todo::sample("Late", false, Some("2024-05-01")),
+            Todo::sample("Later", false, Some("2024-06-01")),
+            Todo::sample("Done", true, Some("2024-05-01")),
+        ];
+        let policy = Policy {
+            max_open: Some(1),
+            max_overdue: Some(1),
+            ..Policy::default()
+        };
+
+        assert_eq!(
+            policy.breaches(&todos, &now()),
+            ["2 open todos exceed the maximum of 1"]
+        );
+    }
+
+    #[test]
+    fn test_overdue_age_limit() {
+        let todos = [
+            // Date-only deadlines fall at the following midnight
+            Todo::sample("Yesterday", false, Some("2024-05-02")),
+            Todo::sample("Last week", false, Some("2024-04-26")),
+            Todo::sample("Done", true, Some("2024-04-01")),
+        ];
+        let policy = Policy {
+            max_overdue_age: Some(Duration::from_secs(72 * 60 * 60)),
+            ..Policy::default()
+        };
+
+        assert_eq!(
+            policy.breaches(&todos, &now()),
+            ["'Last week' is overdue by 6d, more than 3d"]
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use crate::notion::{ApiVersion, SUPPORTED_VERSIONS};
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_HASH: &str = env!("NOTIFIERU_GIT_HASH");
+
+/// Cargo features compiled into this binary
+pub fn features() -> Vec<&'static str> {
+    vec![
+        #[cfg(feature = "age")]
+        "age",
+        #[cfg(feature = "keyring")]
+        "keyring",
+    ]
+}
+
+/// The `version` output; the verbose form is meant to be pasted into bug reports
+pub fn report(verbose: bool, configured_version: ApiVersion) -> String {
+    if !verbose {
+        return format!("notifieru {VERSION}\n");
+    }
+
+    let features = features();
+    let features = if features.is_empty() {
+        "none".to_owned()
+    } else {
+        features.join(", ")
+    };
+
+    let supported: Vec<_> = SUPPORTED_VERSIONS.iter().map(|v| v.as_str()).collect();
+    let supported = supported.join(", ");
+
+    let mut report = format!("notifieru {VERSION}\n");
+    for (label, value) in [
+        ("git hash", GIT_HASH),
+        ("features", &features),
+        ("supported Notion-Version", &supported),
+        ("configured Notion-Version", configured_version.as_str()),
+    ] {
+        report.push_str(&format!("{:27} {value}\n", format!("{label}:")));
+    }
+    report
+}
@@ -0,0 +1,169 @@
+use crate::snapshot::Snapshot;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// The last fetched snapshot of each database, one file per database id
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// A cached snapshot file as found on disk
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub database_id: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+impl Entry {
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.modified)
+            .unwrap_or_default()
+    }
+}
+
+/// `$XDG_CACHE_HOME/notifieru`, falling back to `~/.cache/notifieru`
+pub fn default_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("notifieru"))
+}
+
+impl Cache {
+    pub fn new<T>(dir: T) -> Cache
+    where
+        T: Into<PathBuf>,
+    {
+        Cache { dir: dir.into() }
+    }
+
+    pub fn open_default() -> crate::Result<Cache> {
+        default_dir()
+            .map(Cache::new)
+            .ok_or_else(|| "cannot locate a cache directory, set XDG_CACHE_HOME or HOME".into())
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn store(&self, database_id: &str, snapshot: &Snapshot) -> crate::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        // Write then rename, so a concurrent reader never sees half a file
+        let path = self.path(database_id);
+        let tmp = path.with_extension("json.tmp");
+        snapshot.save(&tmp)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, database_id: &str) -> crate::Result<Option<Snapshot>> {
+        let path = self.path(database_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Snapshot::load(path).map(Some)
+    }
+
+    pub fn entries(&self) -> crate::Result<Vec<Entry>> {
+        let dir = match fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut entries = Vec::new();
+        for file in dir {
+            let path = file?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Some(database_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let metadata = fs::metadata(&path)?;
+            entries.push(Entry {
+                database_id: database_id.to_owned(),
+                size: metadata.len(),
+                modified: metadata.modified()?,
+                path,
+            });
+        }
+        entries.sort_by(|a, b| a.database_id.cmp(&b.database_id));
+        Ok(entries)
+    }
+
+    /// Removes every cached snapshot, returning how many were removed
+    pub fn clear(&self) -> crate::Result<usize> {
+        let entries = self.entries()?;
+        for entry in &entries {
+            fs::remove_file(&entry.path)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Removes snapshots older than `max_age` and ones that no longer load,
+    /// e.g. those written by an incompatible version, returning what was removed
+    pub fn gc(&self, max_age: Duration) -> crate::Result<Vec<Entry>> {
+        let mut removed = Vec::new();
+        for entry in self.entries()? {
+            if entry.age() > max_age || Snapshot::load(&entry.path).is_err() {
+                fs::remove_file(&entry.path)?;
+                removed.push(entry);
+            }
+        }
+        Ok(removed)
+    }
+
+    fn path(&self, database_id: &str) -> PathBuf {
+        self.dir.join(format!("{database_id}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::new(dir.path().join("notifieru"));
+        let snapshot = Snapshot::new(Vec::new());
+
+        assert!(cache.load("db-1").unwrap().is_none());
+        cache.store("db-1", &snapshot).unwrap();
+
+        assert_eq!(cache.load("db-1").unwrap(), Some(snapshot));
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].database_id, "db-1");
+    }
+
+    #[test]
+    fn test_gc_and_clear() {
+        let dir = TempDir::new().unwrap();
+        let cache = Cache::new(dir.path());
+        cache.store("db-1", &Snapshot::new(Vec::new())).unwrap();
+        cache.store("db-2", &Snapshot::new(Vec::new())).unwrap();
+        fs::write(dir.path().join("db-3.json"), "{not a snapshot").unwrap();
+
+        let removed = cache.gc(Duration::from_secs(3600)).unwrap();
+
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].database_id, "db-3");
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,467 @@
+use notifieru::alert::Policy;
+use notifieru::config::Config;
+use notifieru::datetime;
+use notifieru::filter::{Condition, ShowDone, SortKey};
+use notifieru::notion::{self, ApiVersion, Fault};
+use notifieru::render::Format;
+use notifieru::secrets::ApiKeySource;
+use notifieru::todo::PropertyNames;
+
+use chrono::{DateTime, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The default `notify --within`, also used by `list --notify`
+pub const NOTIFY_WITHIN: &str = "1d";
+
+/// Simple & minimal todo notifier for Notion databases
+#[derive(Debug, Parser)]
+#[command(name = "notifieru", version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Running without a subcommand lists todos
+    #[command(flatten)]
+    pub list: ListArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct GlobalArgs {
+    /// Config file to use instead of ~/.config/notifieru/config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Profile from the config file to use, e.g. work
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Path of the secrets file holding DB_URL and API_KEY [default: .secrets]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub secrets_path: Option<PathBuf>,
+
+    /// Notion property a field is read from, e.g. due=Deadline, overriding
+    /// [properties] in the config file; repeatable
+    #[arg(
+        long = "property",
+        global = true,
+        value_name = "FIELD=NAME",
+        value_parser = parse_property
+    )]
+    pub properties: Vec<(String, String)>,
+
+    /// Read the API key from stdin, taking precedence over the environment and
+    /// the secrets file
+    #[arg(long, global = true, conflicts_with = "api_key_file")]
+    pub api_key_stdin: bool,
+
+    /// Read the API key from this file or file descriptor (/dev/fd/N),
+    /// taking precedence over the environment and the secrets file
+    #[arg(long, global = true, value_name = "PATH")]
+    pub api_key_file: Option<PathBuf>,
+
+    /// Print progress and results of list and notify as JSON events, one per
+    /// line, instead of their usual output
+    #[arg(long, global = true)]
+    pub events: bool,
+
+    /// Fail requests taking longer than this, e.g. 10s, overriding timeout in
+    /// the config file [default: 30s]
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration)]
+    pub timeout: Option<Duration>,
+
+    /// Notion API version to send, e.g. 2025-09-03, overriding notion_version
+    /// in the config file
+    #[arg(long, global = true, value_name = "VERSION")]
+    pub notion_version: Option<ApiVersion>,
+
+    /// User-Agent sent with every request
+    #[arg(long, global = true, value_name = "UA")]
+    pub user_agent: Option<String>,
+
+    /// Extra request header, repeatable
+    #[arg(
+        long = "header",
+        global = true,
+        value_name = "NAME: VALUE",
+        value_parser = parse_header
+    )]
+    pub headers: Vec<(String, String)>,
+
+    /// Fake an API failure (rate_limit, timeout, bad_json) instead of sending
+    /// requests; meant for tests and debugging
+    #[arg(long, global = true, hide = true, value_name = "FAULT")]
+    pub inject_fault: Option<Fault>,
+
+    /// Loaded from `--config` or the default location once the flags are
+    /// parsed, with `--profile` applied
+    #[arg(skip)]
+    pub loaded_config: Config,
+}
+
+impl GlobalArgs {
+    /// `--secrets-path`, then the config file, then `.secrets`
+    pub fn secrets_path(&self) -> PathBuf {
+        self.secrets_path
+            .clone()
+            .or_else(|| self.loaded_config.secrets_path.clone())
+            .unwrap_or_else(|| PathBuf::from(".secrets"))
+    }
+
+    /// `--notion-version`, then the config file, then the oldest supported
+    /// version
+    pub fn notion_version(&self) -> ApiVersion {
+        self.notion_version
+            .or(self.loaded_config.notion_version)
+            .unwrap_or_default()
+    }
+
+    pub fn api_key_source(&self) -> Option<ApiKeySource> {
+        match &self.api_key_file {
+            Some(path) => Some(ApiKeySource::File(path.clone())),
+            None if self.api_key_stdin => Some(ApiKeySource::Stdin),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List todos (the default)
+    List(Box<ListArgs>),
+    /// Send desktop notifications for due and overdue todos
+    Notify(NotifyArgs),
+    /// Show the configuration notifieru resolved
+    Config {
+        #[command(subcommand)]
+        command: Option<ConfigCommand>,
+    },
+    /// Check the secrets file, connectivity, database access and schema
+    Doctor,
+    /// Set up the secrets and config files interactively
+    Init(InitArgs),
+    /// Compare the database with the previous listing or a snapshot
+    Diff(DiffArgs),
+    /// Print a markdown report of what was done, what slipped and what's next
+    Review(ReviewArgs),
+    /// Print completion stats, optionally publishing them to a Notion page
+    Stats(StatsArgs),
+    /// Print open and overdue counts for a shell prompt, e.g. ☐3 ⚑1, read
+    /// from the last cached listing without the network
+    Prompt,
+    /// Inspect or clean up cached listings
+    Cache {
+        #[command(subcommand)]
+        command: Option<CacheCommand>,
+    },
+    /// Print version and build information
+    Version {
+        /// Include build details for bug reports
+        #[arg(short, long)]
+        verbose: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Output format: table, json, gha, quickfix, org, widget or alfred
+    #[arg(long, value_name = "FORMAT")]
+    pub format: Option<Format>,
+
+    /// JMESPath expression applied to the JSON output
+    #[arg(long, value_name = "EXPR")]
+    pub query: Option<String>,
+
+    /// Also list done todos, which are left out by default
+    #[arg(long)]
+    pub all: bool,
+
+    /// Which done todos to list otherwise: never, last, or ones done within a
+    /// duration such as 7d; overrides output.show_done in the config file
+    #[arg(long, value_name = "POLICY", conflicts_with = "all")]
+    pub show_done: Option<ShowDone>,
+
+    /// Only list todos due before this date, e.g. 2024-06-01; asked of Notion
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub due_before: Option<String>,
+
+    /// Only list todos due after this date; asked of Notion
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub due_after: Option<String>,
+
+    /// Only list todos with this option of properties.status, a status or
+    /// select; repeatable, any of them matching; asked of Notion
+    #[arg(long = "status", value_name = "OPTION")]
+    pub statuses: Vec<String>,
+
+    /// Only show todos whose title matches this regex
+    #[arg(long = "match", value_name = "REGEX")]
+    pub title: Option<String>,
+
+    /// Only show todos with a tag matching this glob, repeatable
+    #[arg(long = "tag", value_name = "GLOB")]
+    pub tags: Vec<String>,
+
+    /// Only show todos where a property compares to a value, e.g. 'Estimate>=3',
+    /// with =, !=, <, <=, > or >=; repeatable
+    #[arg(long = "filter", value_name = "EXPR")]
+    pub conditions: Vec<Condition>,
+
+    /// Sort by a property, e.g. Estimate:desc; repeatable, the first key wins
+    #[arg(long = "sort", value_name = "PROPERTY[:asc|:desc]")]
+    pub sort: Vec<SortKey>,
+
+    /// Add a table column showing a property, e.g. Assignee; repeatable
+    #[arg(long = "show-property", value_name = "NAME")]
+    pub show_properties: Vec<String>,
+
+    /// Cut table titles to this many characters; fits the terminal by default
+    #[arg(long, value_name = "N")]
+    pub max_width: Option<usize>,
+
+    /// Wrap long table titles across lines instead of cutting them
+    #[arg(long)]
+    pub wrap: bool,
+
+    /// Show at most this many table rows
+    #[arg(long, value_name = "N")]
+    pub rows: Option<usize>,
+
+    /// Also write the listing to a file, e.g. json:todos.json; repeatable
+    #[arg(long = "export", value_name = "FORMAT:PATH", value_parser = parse_export)]
+    pub exports: Vec<(Format, PathBuf)>,
+
+    /// Also raise the desktop notifications `notify` would
+    #[arg(long)]
+    pub notify: bool,
+
+    #[command(flatten)]
+    pub thresholds: ThresholdArgs,
+}
+
+/// Limits that make the run exit with status 2 when breached
+#[derive(Debug, Args)]
+pub struct ThresholdArgs {
+    /// Alert when more todos than this are open
+    #[arg(long, value_name = "N")]
+    pub max_open: Option<usize>,
+
+    /// Alert when more todos than this are overdue
+    #[arg(long, value_name = "N")]
+    pub max_overdue: Option<usize>,
+
+    /// Alert when any todo is overdue for longer than this, e.g. 72h
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub max_overdue_age: Option<Duration>,
+}
+
+impl ThresholdArgs {
+    pub fn policy(&self) -> Policy {
+        Policy {
+            max_open: self.max_open,
+            max_overdue: self.max_overdue,
+            max_overdue_age: self.max_overdue_age,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct NotifyArgs {
+    /// Also notify about todos due within this long, e.g. 30m or 1d
+    #[arg(long, value_name = "DURATION", default_value = NOTIFY_WITHIN, value_parser = parse_duration)]
+    pub within: Duration,
+
+    /// Print the notifications instead of sending them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    #[command(flatten)]
+    pub thresholds: ThresholdArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct InitArgs {
+    /// Overwrite an existing secrets file
+    #[arg(long)]
+    pub force: bool,
+
+    /// Notion API base URL, e.g. of a proxy
+    #[arg(long, value_name = "URL", default_value = notion::API_BASE_URL)]
+    pub api_base_url: String,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Snapshot file to compare against instead of the cached listing
+    #[arg(long, value_name = "PATH")]
+    pub against: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReviewArgs {
+    /// Review the last day
+    #[arg(long, conflicts_with = "week")]
+    pub day: bool,
+
+    /// Review the last week (the default)
+    #[arg(long)]
+    pub week: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    /// Write the stats onto this Notion page, replacing an earlier publish
+    #[arg(long, value_name = "PAGE_ID")]
+    pub publish: Option<String>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the resolved configuration (the default)
+    Show,
+    /// Store the API key read from stdin in the OS keychain
+    SetKey,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum CacheCommand {
+    /// Show cached databases with their size and age (the default)
+    Status,
+    /// Remove every cached listing
+    Clear,
+    /// Remove stale and unreadable cached listings
+    Gc {
+        /// Remove listings older than this
+        #[arg(long, value_name = "DURATION", default_value = "30d", value_parser = parse_duration)]
+        max_age: Duration,
+    },
+}
+
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    match header.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(format!("invalid header '{header}', expected 'Name: value'")),
+    }
+}
+
+fn parse_property(property: &str) -> Result<(String, String), String> {
+    match property.split_once('=') {
+        Some((field, name)) if !name.trim().is_empty() => {
+            let (field, name) = (field.trim(), name.trim());
+            // Rejects unknown fields while parsing, like any other bad flag
+            PropertyNames::default().set(field, name)?;
+            Ok((field.to_owned(), name.to_owned()))
+        }
+        _ => Err(format!(
+            "invalid property '{property}', expected e.g. 'due=Deadline'"
+        )),
+    }
+}
+
+fn parse_export(export: &str) -> Result<(Format, PathBuf), String> {
+    match export.split_once(':') {
+        Some((format, path)) if !path.is_empty() => Ok((format.parse()?, PathBuf::from(path))),
+        _ => Err(format!("invalid export '{export}', expected FORMAT:PATH")),
+    }
+}
+
+/// Dates and datetimes as Notion filters take them, checked up front so a typo
+/// isn't reported as a failed request
+fn parse_date(s: &str) -> Result<String, String> {
+    let valid =
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok() || DateTime::parse_from_rfc3339(s).is_ok();
+    if valid {
+        Ok(s.to_owned())
+    } else {
+        Err(format!("invalid date '{s}', expected e.g. 2024-06-01"))
+    }
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    datetime::parse_duration(s).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_list_is_the_default() {
+        let cli = Cli::parse_from(["notifieru", "--format", "json", "--tag", "work"]);
+
+        assert!(cli.command.is_none());
+        assert_eq!(cli.list.format, Some(Format::Json));
+        assert_eq!(cli.list.tags, ["work"]);
+    }
+
+    #[test]
+    fn test_global_args_after_subcommand() {
+        let cli = Cli::parse_from([
+            "notifieru",
+            "doctor",
+            "--secrets-path",
+            "/tmp/secrets",
+            "--header",
+            "X-Tenant: home",
+        ]);
+
+        assert!(matches!(cli.command, Some(Command::Doctor)));
+        assert_eq!(cli.global.secrets_path(), PathBuf::from("/tmp/secrets"));
+        assert_eq!(
+            cli.global.headers,
+            [("X-Tenant".to_owned(), "home".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_parse_export() {
+        assert_eq!(
+            parse_export("json:out/todos.json"),
+            Ok((Format::Json, PathBuf::from("out/todos.json")))
+        );
+        assert!(parse_export("json").is_err());
+        assert!(parse_export("json:").is_err());
+        assert!(parse_export("csv:todos.csv").is_err());
+    }
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("X-Trace:abc"),
+            Ok(("X-Trace".to_owned(), "abc".to_owned()))
+        );
+        assert!(parse_header("X-Trace").is_err());
+        assert!(parse_header(": abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_property() {
+        assert_eq!(
+            parse_property("due = Deadline"),
+            Ok(("due".to_owned(), "Deadline".to_owned()))
+        );
+        assert!(parse_property("deadline=Deadline").is_err());
+        assert!(parse_property("due=").is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert!(parse_date("2024-06-01").is_ok());
+        assert!(parse_date("2024-06-01T09:00:00+09:00").is_ok());
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("tomorrow").is_err());
+    }
+}
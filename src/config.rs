@@ -0,0 +1,380 @@
+use crate::datetime;
+use crate::filter::ShowDone;
+use crate::notion::{ApiVersion, Direction};
+use crate::number::Locale;
+use crate::render::Format;
+use crate::todo::{self, DoneWhen, PropertyNames};
+use crate::Error;
+
+use serde::{Deserialize, Deserializer};
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Settings from `config.toml`. Every field is optional and command-line flags
+/// take precedence over all of them.
+///
+/// ```toml
+/// database_url = "https://api.notion.com/v1/databases/<id>/query"
+/// secrets_path = "/home/me/.config/notifieru/secrets"
+/// age_identity = "/home/me/.config/notifieru/key.txt"
+/// max_response_bytes = 4194304
+/// max_pages = 20
+/// max_attempts = 5
+/// timeout = "1m"
+/// # Instead of HTTPS_PROXY, which is read otherwise; NO_PROXY still applies
+/// proxy = "http://proxy.example.com:3128"
+/// # Unless --notion-version says otherwise
+/// notion_version = "2025-09-03"
+/// # Unless --user-agent or --header say otherwise
+/// user_agent = "gateway-client/2"
+/// headers = { "X-Tenant" = "household" }
+/// # When properties.done is a status rather than a checkbox
+/// done_when = { status = ["Done", "Cancelled"] }
+///
+/// [properties]
+/// title = "Task"
+/// done = "Status"
+/// due = "Deadline"
+/// extra = ["Estimate"]
+///
+/// [sort]
+/// direction = "descending"
+///
+/// [output]
+/// format = "json"
+/// columns = ["title", "due", "Estimate"]
+/// locale = "de"
+/// # Also list todos done in the past week, by properties.completed
+/// show_done = "7d"
+///
+/// # Picked with `--profile`, or `default_profile = "work"`
+/// [profiles.work]
+/// database_url = "https://api.notion.com/v1/databases/<other id>/query"
+/// secrets_path = "/home/me/.config/notifieru/work.secrets"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Takes precedence over `DB_URL` in the secrets file, which can then
+    /// leave it out
+    pub database_url: Option<String>,
+    /// Where the secrets file is when `--secrets-path` isn't given
+    pub secrets_path: Option<PathBuf>,
+    /// Decrypts an age-encrypted secrets file, see `secrets::read_secrets`
+    pub age_identity: Option<PathBuf>,
+    pub properties: PropertyNames,
+    pub done_when: DoneWhen,
+    pub sort: Sort,
+    pub output: Output,
+    /// Responses with a larger body fail, 16 MiB by default
+    pub max_response_bytes: Option<u64>,
+    /// Listings stop after this many pages of 100 todos, 100 by default
+    pub max_pages: Option<usize>,
+    /// Requests Notion rate limits or fails with a 5xx are tried this many
+    /// times in all, 3 by default
+    pub max_attempts: Option<u32>,
+    /// How long a request may take, connecting included, 30s by default
+    #[serde(deserialize_with = "duration")]
+    pub timeout: Option<Duration>,
+    /// HTTP proxy requests to Notion go through, taking precedence over
+    /// `HTTPS_PROXY` and the like
+    pub proxy: Option<String>,
+    /// The `Notion-Version` to send unless `--notion-version` is given, the
+    /// oldest supported one by default
+    pub notion_version: Option<ApiVersion>,
+    /// Sent as the User-Agent unless `--user-agent` is given
+    pub user_agent: Option<String>,
+    /// Sent with every request, after the built-in headers so they can
+    /// override them. A `--header` of the same name replaces one.
+    pub headers: BTreeMap<String, String>,
+    /// The profile to use when `--profile` isn't given
+    pub default_profile: Option<String>,
+    pub profiles: BTreeMap<String, Profile>,
+    /// The profile `with_profile` applied
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+/// A database with its own secrets, e.g. in another workspace, overriding the
+/// settings of the same name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub database_url: Option<String>,
+    pub secrets_path: Option<PathBuf>,
+    pub age_identity: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sort {
+    /// Defaults to the due date property
+    pub property: Option<String>,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Output {
+    pub format: Option<Format>,
+    /// Table columns, built-in fields or any other property; title and due
+    /// date by default
+    pub columns: Vec<String>,
+    /// Separators numbers are shown with, English by default
+    pub locale: Option<Locale>,
+    /// Done todos listed without `--all`: `never`, the default, `last` or a
+    /// duration such as `7d`, which reads when they were done from the
+    /// `completed` property
+    pub show_done: Option<ShowDone>,
+}
+
+/// `$XDG_CONFIG_HOME/notifieru/config.toml`, falling back to
+/// `~/.config/notifieru/config.toml`
+pub fn default_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("notifieru").join("config.toml"))
+}
+
+impl Config {
+    pub fn load<T>(path: T) -> crate::Result<Config>
+    where
+        T: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("failed to read '{}': {e}", path.display())))?;
+        Config::parse(&content)
+            .map_err(|e| Error::Config(format!("invalid '{}': {e}", path.display())))
+    }
+
+    /// The config at `default_path`, or the defaults when there is none
+    pub fn load_default() -> crate::Result<Config> {
+        match default_path() {
+            Some(path) if path.exists() => Config::load(path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn parse(content: &str) -> crate::Result<Config> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Applies the profile `name`, or else `default_profile` if set
+    pub fn with_profile(mut self, name: Option<&str>) -> crate::Result<Config> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(self);
+        };
+        let Some(profile) = self.profiles.get(name) else {
+            let known: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+            return Err(Error::Config(match known.as_slice() {
+                [] => format!("unknown profile '{name}', the config file defines none"),
+                known => format!(
+                    "unknown profile '{name}', expected one of {}",
+                    known.join(", ")
+                ),
+            }));
+        };
+
+        let profile = profile.clone();
+        self.database_url = profile.database_url.or(self.database_url);
+        self.secrets_path = profile.secrets_path.or(self.secrets_path);
+        self.age_identity = profile.age_identity.or(self.age_identity);
+        self.profile = Some(name.to_owned());
+        Ok(self)
+    }
+
+    /// The properties to read, including those only `output.columns` names,
+    /// with `done_when` telling when the done property means done
+    pub fn property_names(&self) -> PropertyNames {
+        let mut names = self.properties.clone();
+        names.done_when = self.done_when.clone();
+        for column in &self.output.columns {
+            if !todo::FIELDS.contains(&column.as_str()) && !names.extra.contains(column) {
+                names.extra.push(column.clone());
+            }
+        }
+        names
+    }
+
+    /// The property todos are sorted by
+    pub fn sort_property(&self) -> &str {
+        self.sort
+            .property
+            .as_deref()
+            .unwrap_or(&self.properties.due)
+    }
+}
+
+/// A duration written like `--within`, e.g. `90s` or `2m`
+fn duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(s) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    datetime::parse_duration(&s)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty() {
+        let config = Config::parse("").unwrap();
+
+        assert_eq!(config, Config::default());
+        assert_eq!(config.sort_property(), "Due");
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r#"
+database_url = "https://api.notion.com/v1/databases/db-1/query"
+
+[properties]
+title = "Task"
+due = "Deadline"
+
+[sort]
+direction = "descending"
+
+[output]
+format = "json"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.database_url.as_deref(),
+            Some("https://api.notion.com/v1/databases/db-1/query")
+        );
+        assert_eq!(config.properties.title, "Task");
+        assert_eq!(config.properties.done, "Done");
+        assert_eq!(config.sort_property(), "Deadline");
+        assert_eq!(config.sort.direction, Direction::Descending);
+        assert_eq!(config.output.format, Some(Format::Json));
+        assert!(config.property_names().extra.is_empty());
+    }
+
+    #[test]
+    fn test_parse_user_agent_and_headers() {
+        let config = Config::parse(
+            r#"
+user_agent = "gateway-client/2"
+
+[headers]
+X-Tenant = "household"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.user_agent.as_deref(), Some("gateway-client/2"));
+        assert_eq!(
+            config.headers,
+            BTreeMap::from([("X-Tenant".to_owned(), "household".to_owned())])
+        );
+    }
+
+    #[test]
+    fn test_parse_notion_version() {
+        let config = Config::parse("notion_version = \"2025-09-03\"\n").unwrap();
+        assert_eq!(config.notion_version, Some(ApiVersion::V2025_09_03));
+
+        let err = Config::parse("notion_version = \"2021-05-13\"\n").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unsupported Notion-Version '2021-05-13'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        let config = Config::parse("timeout = \"90s\"\n").unwrap();
+        assert_eq!(config.timeout, Some(Duration::from_secs(90)));
+
+        let err = Config::parse("timeout = \"soon\"\n").unwrap_err();
+        assert!(err.to_string().contains("invalid duration 'soon'"), "{err}");
+    }
+
+    #[test]
+    fn test_property_names_include_columns() {
+        let config = Config::parse(
+            r#"
+done_when = { status = ["Done", "Cancelled"] }
+
+[properties]
+extra = ["Estimate"]
+
+[output]
+columns = ["title", "Estimate", "Context", "tags"]
+"#,
+        )
+        .unwrap();
+
+        let names = config.property_names();
+        assert_eq!(names.extra, ["Estimate", "Context"]);
+        assert_eq!(names.done_when.status, ["Done", "Cancelled"]);
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let config = Config::parse(
+            r#"
+database_url = "https://api.notion.com/v1/databases/personal/query"
+secrets_path = "personal.secrets"
+default_profile = "work"
+
+[profiles.work]
+database_url = "https://api.notion.com/v1/databases/work/query"
+
+[profiles.personal]
+"#,
+        )
+        .unwrap();
+
+        let work = config.clone().with_profile(None).unwrap();
+        assert_eq!(work.profile.as_deref(), Some("work"));
+        assert_eq!(
+            work.database_url.as_deref(),
+            Some("https://api.notion.com/v1/databases/work/query")
+        );
+        // What the profile leaves out comes from the top level
+        assert_eq!(work.secrets_path, Some(PathBuf::from("personal.secrets")));
+
+        let personal = config.clone().with_profile(Some("personal")).unwrap();
+        assert_eq!(
+            personal.database_url.as_deref(),
+            Some("https://api.notion.com/v1/databases/personal/query")
+        );
+
+        assert_eq!(
+            config.with_profile(Some("home")).unwrap_err().to_string(),
+            "unknown profile 'home', expected one of personal, work"
+        );
+        assert!(Config::default()
+            .with_profile(None)
+            .unwrap()
+            .profile
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_keys() {
+        assert!(Config::parse("databse_url = \"typo\"").is_err());
+        assert!(Config::parse("[output]\nlocale = \"xx\"").is_err());
+        assert!(Config::parse("[output]\nformat = \"xml\"").is_err());
+    }
+}
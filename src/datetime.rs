@@ -0,0 +1,272 @@
+use crate::todo::DateRange;
+
+use chrono::{DateTime, NaiveDate, TimeZone};
+
+use std::time::Duration;
+
+/// Appends a Notion date or datetime in `YYYY-MM-DD[ HH:MM[:SS]]` form.
+///
+/// Fractional seconds and UTC offsets are dropped. Anything that doesn't look
+/// like an ISO 8601 datetime is appended as-is rather than guessed at.
+pub fn push_datetime(datetime: &str, buf: &mut String) {
+    match datetime.split_once('T') {
+        Some((ymd, t)) => {
+            let time_len = t
+                .bytes()
+                .take(8)
+                .take_while(|b| b.is_ascii_digit() || *b == b':')
+                .count();
+
+            if ymd.is_empty() || time_len == 0 {
+                buf.push_str(datetime);
+            } else {
+                buf.push_str(&format!("{} {}", ymd, &t[..time_len]));
+            }
+        }
+        None => buf.push_str(datetime),
+    }
+}
+
+pub fn format_datetime(datetime: &str) -> String {
+    let mut buf = String::new();
+    push_datetime(datetime, &mut buf);
+    buf
+}
+
+/// Appends a date range as `start ~ end`, or just `start` for a single date
+pub fn push_date_range(range: &DateRange, buf: &mut String) {
+    push_datetime(&range.start, buf);
+    if let Some(end) = &range.end {
+        buf.push_str(" ~ ");
+        push_datetime(end, buf);
+    }
+}
+
+/// The instant a Notion date is considered passed in `tz`: the time itself for
+/// datetimes, the following midnight for date-only values
+pub fn deadline<Tz>(datetime: &str, tz: &Tz) -> Option<DateTime<Tz>>
+where
+    Tz: TimeZone,
+{
+    if let Ok(dt) = DateTime::parse_from_rfc3339(datetime) {
+        return Some(dt.with_timezone(tz));
+    }
+
+    let date = NaiveDate::parse_from_str(datetime, "%Y-%m-%d").ok()?;
+    let midnight = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+    tz.from_local_datetime(&midnight).earliest()
+}
+
+/// The instant a Notion date begins in `tz`: the time itself for datetimes,
+/// midnight for date-only values
+pub fn starts_at<Tz>(datetime: &str, tz: &Tz) -> Option<DateTime<Tz>>
+where
+    Tz: TimeZone,
+{
+    if let Ok(dt) = DateTime::parse_from_rfc3339(datetime) {
+        return Some(dt.with_timezone(tz));
+    }
+
+    let date = NaiveDate::parse_from_str(datetime, "%Y-%m-%d").ok()?;
+    tz.from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+}
+
+/// An Org mode timestamp such as `<2024-05-01 Wed>` or `<2024-05-01 Wed 09:30>`.
+/// Like `push_datetime`, datetimes keep the wall-clock time they were written
+/// in, as Org timestamps have no offset.
+pub fn org_timestamp(datetime: &str) -> Option<String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(datetime) {
+        return Some(dt.format("<%Y-%m-%d %a %H:%M>").to_string());
+    }
+
+    let date = NaiveDate::parse_from_str(datetime, "%Y-%m-%d").ok()?;
+    Some(date.format("<%Y-%m-%d %a>").to_string())
+}
+
+/// Parses a human duration such as `90s`, `15m`, `72h`, `7d` or `2w`
+pub fn parse_duration(s: &str) -> crate::Result<Duration> {
+    let invalid = || format!("invalid duration '{s}', expected e.g. 30m, 72h or 7d");
+
+    let unit_at = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = s.split_at(unit_at);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid().into()),
+    };
+
+    let secs = amount.checked_mul(secs).ok_or_else(invalid)?;
+    Ok(Duration::from_secs(secs))
+}
+
+/// Renders a duration in its largest whole unit, e.g. `5m` or `3d`
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_format_datetime() {
+        assert_eq!(
+            format_datetime("2024-05-01T09:30:00.000+09:00"),
+            "2024-05-01 09:30:00"
+        );
+        assert_eq!(format_datetime("2024-05-01T09:30Z"), "2024-05-01 09:30");
+        assert_eq!(format_datetime("2024-05-01"), "2024-05-01");
+        assert_eq!(format_datetime("2024-05-01T"), "2024-05-01T");
+    }
+
+    #[test]
+    fn test_deadline() {
+        use chrono::{FixedOffset, Utc};
+
+        let kst = FixedOffset::east_opt(9 * 3600).unwrap();
+        assert_eq!(
+            deadline("2024-05-01T09:30:00.000+09:00", &Utc)
+                .unwrap()
+                .to_rfc3339(),
+            "2024-05-01T00:30:00+00:00"
+        );
+        assert_eq!(
+            deadline("2024-05-01", &kst).unwrap().to_rfc3339(),
+            "2024-05-02T00:00:00+09:00"
+        );
+        assert_eq!(deadline("next friday", &Utc), None);
+        assert_eq!(
+            starts_at("2024-05-01", &kst).unwrap().to_rfc3339(),
+            "2024-05-01T00:00:00+09:00"
+        );
+    }
+
+    #[test]
+    fn test_org_timestamp() {
+        assert_eq!(
+            org_timestamp("2024-05-02T09:30:00.000+09:00").as_deref(),
+            Some("<2024-05-02 Thu 09:30>")
+        );
+        assert_eq!(
+            org_timestamp("2024-05-01").as_deref(),
+            Some("<2024-05-01 Wed>")
+        );
+        assert_eq!(org_timestamp("next friday"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(
+            parse_duration("72h").unwrap(),
+            Duration::from_secs(72 * 3600)
+        );
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(14 * 86400)
+        );
+        assert_eq!(
+            parse_duration("7").unwrap_err().to_string(),
+            "invalid duration '7', expected e.g. 30m, 72h or 7d"
+        );
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        assert_eq!(
+            parse_duration("18446744073709551615d")
+                .unwrap_err()
+                .to_string(),
+            "invalid duration '18446744073709551615d', expected e.g. 30m, 72h or 7d"
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615s").unwrap(),
+            Duration::from_secs(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(300)), "5m");
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2h");
+        assert_eq!(format_duration(Duration::from_secs(3 * 86400 + 5)), "3d");
+    }
+
+    prop_compose! {
+        fn ymd()(y in 1900u32..2200, m in 1u32..=12, d in 1u32..=28) -> String {
+            format!("{y:04}-{m:02}-{d:02}")
+        }
+    }
+
+    prop_compose! {
+        fn hms()(h in 0u32..24, m in 0u32..60, s in 0u32..60) -> String {
+            format!("{h:02}:{m:02}:{s:02}")
+        }
+    }
+
+    fn offset() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("Z".to_owned()),
+            (-12i32..=14, prop_oneof![Just(0u32), Just(30), Just(45)]).prop_map(|(h, m)| format!(
+                "{}{:02}:{m:02}",
+                if h < 0 { '-' } else { '+' },
+                h.abs()
+            )),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_never_panics(s in any::<String>()) {
+            format_datetime(&s);
+        }
+
+        #[test]
+        fn prop_never_panics_around_separator(a in "\\PC*", b in "\\PC{0,10}") {
+            format_datetime(&format!("{a}T{b}"));
+        }
+
+        #[test]
+        fn prop_range_renders_both_ends(start in ymd(), end in proptest::option::of(ymd())) {
+            let range = DateRange { start: start.clone(), end: end.clone() };
+            let mut buf = String::new();
+            push_date_range(&range, &mut buf);
+
+            match end {
+                Some(end) => prop_assert_eq!(buf, format!("{start} ~ {end}")),
+                None => prop_assert_eq!(buf, start),
+            }
+        }
+
+        #[test]
+        fn prop_date_only_is_unchanged(date in ymd()) {
+            prop_assert_eq!(format_datetime(&date), date);
+        }
+
+        #[test]
+        fn prop_datetime_drops_fraction_and_offset(
+            date in ymd(),
+            time in hms(),
+            fraction in proptest::option::of("\\.[0-9]{1,6}"),
+            offset in offset(),
+        ) {
+            let datetime = format!("{date}T{time}{}{offset}", fraction.unwrap_or_default());
+            prop_assert_eq!(format_datetime(&datetime), format!("{date} {time}"));
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::todo::Todo;
+
+use std::collections::HashMap;
+
+/// What changed between two listings of the same database, matched by page id
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff {
+    pub added: Vec<Todo>,
+    pub removed: Vec<Todo>,
+    pub completed: Vec<Todo>,
+    pub reopened: Vec<Todo>,
+    /// `(before, after)` pairs whose due date changed
+    pub rescheduled: Vec<(Todo, Todo)>,
+}
+
+impl Diff {
+    pub fn between(before: &[Todo], after: &[Todo]) -> Diff {
+        let old: HashMap<&str, &Todo> = before.iter().map(|t| (t.id.as_str(), t)).collect();
+        let new: HashMap<&str, &Todo> = after.iter().map(|t| (t.id.as_str(), t)).collect();
+
+        let mut diff = Diff::default();
+        for todo in after {
+            let Some(prev) = old.get(todo.id.as_str()) else {
+                diff.added.push(todo.clone());
+                continue;
+            };
+
+            if !prev.done && todo.done {
+                diff.completed.push(todo.clone());
+            } else if prev.done && !todo.done {
+                diff.reopened.push(todo.clone());
+            }
+            if prev.due != todo.due {
+                diff.rescheduled.push(((*prev).clone(), todo.clone()));
+            }
+        }
+        diff.removed = before
+            .iter()
+            .filter(|todo| !new.contains_key(todo.id.as_str()))
+            .cloned()
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.completed.is_empty()
+            && self.reopened.is_empty()
+            && self.rescheduled.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_between() {
+        let before = vec![
+            Todo::sample("1", false, None),
+            Todo::sample("2", false, Some("2024-05-01")),
+            Todo::sample("3", true, None),
+            Todo::sample("4", false, None),
+        ];
+        let after = vec![
+            Todo::sample("1", true, None),
+            Todo::sample("2", false, Some("2024-05-03")),
+            Todo::sample("3", false, None),
+            Todo::sample("5", false, None),
+        ];
+
+        let diff = Diff::between(&before, &after);
+
+        assert_eq!(diff.added, vec![Todo::sample("5", false, None)]);
+        assert_eq!(diff.removed, vec![Todo::sample("4", false, None)]);
+        assert_eq!(diff.completed, vec![Todo::sample("1", true, None)]);
+        assert_eq!(diff.reopened, vec![Todo::sample("3", false, None)]);
+        assert_eq!(
+            diff.rescheduled,
+            vec![(before[1].clone(), after[1].clone())]
+        );
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let todos = vec![Todo::sample("1", false, Some("2024-05-01"))];
+
+        assert!(Diff::between(&todos, &todos).is_empty());
+    }
+}
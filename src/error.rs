@@ -0,0 +1,123 @@
+use std::io;
+
+/// Any error of notifieru, by what failed, so that callers and shell scripts
+/// (through `exit_code`) can tell e.g. a broken config from a Notion outage
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The config file can't be read or doesn't make sense
+    #[error("{0}")]
+    Config(String),
+    /// The secrets file or another source of `DB_URL` and `API_KEY` is missing
+    /// or malformed
+    #[error("{0}")]
+    Secrets(String),
+    /// A request didn't get a readable response, e.g. it timed out
+    #[error(transparent)]
+    Http(Box<dyn std::error::Error + Send + Sync>),
+    /// Notion answered with an error object, e.g. `unauthorized`, and `hint`
+    /// says what to do about it where its message doesn't
+    #[error("notion returned {code}: {message}{}", .hint.map(|hint| format!("; {hint}")).unwrap_or_default())]
+    Api {
+        code: String,
+        message: String,
+        hint: Option<&'static str>,
+    },
+    /// A page or the database lacks a property notifieru reads, or has it
+    /// with another type
+    #[error("{0}")]
+    Schema(String),
+    /// The todos breached a limit, e.g. `--max-open`, one description each
+    #[error("threshold exceeded: {}", .0.join(", "))]
+    Threshold(Vec<String>),
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// The exit status of the binary failing with this error, see the exit
+    /// status section of its docs
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Other(_) => 1,
+            Error::Threshold(_) => 2,
+            Error::Config(_) => 3,
+            Error::Secrets(_) => 4,
+            Error::Http(_) => 5,
+            Error::Api { .. } => 6,
+            Error::Schema(_) => 7,
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Error {
+        Error::Other(message.into())
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Error {
+        Error::Other(message.into())
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Other(error.into())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Error {
+        Error::Other(error.into())
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Error {
+        Error::Config(error.to_string())
+    }
+}
+
+impl From<minreq::Error> for Error {
+    fn from(error: minreq::Error) -> Error {
+        Error::Http(error.into())
+    }
+}
+
+#[cfg(feature = "age")]
+impl From<age::DecryptError> for Error {
+    fn from(error: age::DecryptError) -> Error {
+        Error::Secrets(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = Error::Api {
+            code: "unauthorized".to_owned(),
+            message: "API token is invalid.".to_owned(),
+            hint: Some("check API_KEY"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "notion returned unauthorized: API token is invalid.; check API_KEY"
+        );
+        assert_eq!(error.exit_code(), 6);
+
+        let error = Error::Threshold(vec!["3 open > 2".to_owned(), "1 overdue > 0".to_owned()]);
+        assert_eq!(
+            error.to_string(),
+            "threshold exceeded: 3 open > 2, 1 overdue > 0"
+        );
+        assert_eq!(error.exit_code(), 2);
+
+        let error = Error::from("no todos");
+        assert_eq!(error.to_string(), "no todos");
+        assert_eq!(error.exit_code(), 1);
+    }
+}
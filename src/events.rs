@@ -0,0 +1,62 @@
+use crate::todo::Todo;
+
+use serde::Serialize;
+
+/// A line of the `--events` stream, for wrappers following a run as it goes
+/// rather than parsing its final output
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    FetchStarted {
+        database_id: &'a str,
+    },
+    /// A todo as the JSON format writes it, next to the `event` key
+    Todo(&'a Todo),
+    Warning {
+        message: &'a str,
+    },
+    FetchCompleted {
+        database_id: &'a str,
+        count: usize,
+    },
+    /// A desktop notification, or with `dry_run` one that would have been
+    NotificationSent {
+        summary: &'a str,
+        body: &'a str,
+        dry_run: bool,
+    },
+}
+
+impl Event<'_> {
+    /// The event as a single line of JSON, without the newline
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("events always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_to_line() {
+        let todo = Todo::sample("Pay rent", false, None);
+
+        let line = Event::Todo(&todo).to_line();
+        assert!(!line.contains('\n'));
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["event"], "todo");
+        assert_eq!(value["title"], "Pay rent");
+
+        let line = Event::FetchCompleted {
+            database_id: "db-1",
+            count: 2,
+        }
+        .to_line();
+        assert_eq!(
+            serde_json::from_str::<Value>(&line).unwrap(),
+            json!({"event": "fetch_completed", "database_id": "db-1", "count": 2})
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use crate::datetime;
+use crate::notion::Direction;
+use crate::todo::Todo;
+
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Longest pattern accepted from the command line
+const MAX_PATTERN_LEN: usize = 256;
+/// Upper bound on the compiled program, keeping matching cheap on small devices
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Client-side filtering for what Notion's filter API can't express.
+///
+/// The regex engine guarantees linear-time matching, so a pattern can't stall
+/// the run no matter how it is written.
+#[derive(Debug, Clone, Default)]
+pub struct TodoFilter {
+    title: Option<Regex>,
+    tags: Vec<Regex>,
+    conditions: Vec<Condition>,
+}
+
+impl TodoFilter {
+    pub fn new() -> TodoFilter {
+        TodoFilter::default()
+    }
+
+    /// Keeps todos whose title matches `pattern` anywhere
+    pub fn title_regex(mut self, pattern: &str) -> crate::Result<TodoFilter> {
+        self.title = Some(compile(pattern, pattern)?);
+        Ok(self)
+    }
+
+    /// Keeps todos with at least one tag matching `glob` as a whole, where `*`
+    /// matches any run of characters and `?` any single one. Adding several
+    /// globs requires all of them to match.
+    pub fn tag_glob(mut self, glob: &str) -> crate::Result<TodoFilter> {
+        let mut pattern = String::from("^");
+        for c in glob.chars() {
+            match c {
+                '*' => pattern.push_str(".*"),
+                '?' => pattern.push('.'),
+                c => pattern.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        pattern.push('$');
+
+        self.tags.push(compile(glob, &pattern)?);
+        Ok(self)
+    }
+
+    /// Keeps todos whose property satisfies `condition`. Adding several
+    /// conditions requires all of them to hold.
+    pub fn condition(mut self, condition: Condition) -> TodoFilter {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.tags.is_empty() && self.conditions.is_empty()
+    }
+
+    pub fn matches(&self, todo: &Todo) -> bool {
+        let title = self
+            .title
+            .as_ref()
+            .is_none_or(|re| re.is_match(&todo.title));
+        let tags = self
+            .tags
+            .iter()
+            .all(|re| todo.tags.iter().any(|tag| re.is_match(tag)));
+        let conditions = self.conditions.iter().all(|c| c.holds(todo));
+        title && tags && conditions
+    }
+
+    pub fn apply(&self, todos: Vec<Todo>) -> Vec<Todo> {
+        todos
+            .into_iter()
+            .filter(|todo| self.matches(todo))
+            .collect()
+    }
+}
+
+/// A `PROPERTY OP VALUE` comparison, e.g. `Estimate>=3` or `due<2024-06-01`,
+/// over a property as `Todo::property` names it. `VALUE` is read as the
+/// property's type, and todos where that fails or the property is empty never
+/// match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    property: String,
+    op: Op,
+    value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering.is_eq(),
+            Op::Ne => ordering.is_ne(),
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            Op::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+impl Condition {
+    /// The property the condition is on
+    pub fn property(&self) -> &str {
+        &self.property
+    }
+
+    pub fn holds(&self, todo: &Todo) -> bool {
+        let Some(actual) = todo.property(&self.property) else {
+            return false;
+        };
+        actual
+            .parse_like(&self.value)
+            .and_then(|expected| actual.compare(&expected))
+            .is_some_and(|ordering| self.op.holds(ordering))
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Condition, String> {
+        let invalid = || format!("invalid filter '{s}', expected e.g. 'Estimate>=3'");
+        let at = s.find(['=', '!', '<', '>']).ok_or_else(invalid)?;
+        let (property, rest) = s.split_at(at);
+        let (op, value) = [
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("=", Op::Eq),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ]
+        .into_iter()
+        .find_map(|(token, op)| rest.strip_prefix(token).map(|value| (op, value)))
+        .ok_or_else(invalid)?;
+
+        let (property, value) = (property.trim(), value.trim());
+        if property.is_empty() || value.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Condition {
+            property: property.to_owned(),
+            op,
+            value: value.to_owned(),
+        })
+    }
+}
+
+/// A `PROPERTY[:asc|:desc]` key to sort todos by, naming a property as
+/// `Todo::property` does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub property: String,
+    pub direction: Direction,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SortKey, String> {
+        let (property, direction) = match s.rsplit_once(':') {
+            Some((property, "asc")) => (property, Direction::Ascending),
+            Some((property, "desc")) => (property, Direction::Descending),
+            Some(_) => return Err(format!("invalid sort '{s}', expected PROPERTY[:asc|:desc]")),
+            None => (s, Direction::Ascending),
+        };
+        if property.trim().is_empty() {
+            return Err(format!("invalid sort '{s}', expected PROPERTY[:asc|:desc]"));
+        }
+        Ok(SortKey {
+            property: property.trim().to_owned(),
+            direction,
+        })
+    }
+}
+
+/// Which done todos a listing shows when `--all` isn't given
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ShowDone {
+    /// Only open todos
+    #[default]
+    Never,
+    /// Every done todo, after the open ones
+    Last,
+    /// Done todos whose `completed` date property is at most this long ago,
+    /// after the open ones
+    Within(Duration),
+}
+
+impl FromStr for ShowDone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ShowDone, String> {
+        match s {
+            "never" => Ok(ShowDone::Never),
+            "last" => Ok(ShowDone::Last),
+            s => datetime::parse_duration(s)
+                .map(ShowDone::Within)
+                .map_err(|_| {
+                    format!(
+                        "invalid show_done '{s}', expected never, last or a duration such as 7d"
+                    )
+                }),
+        }
+    }
+}
+
+impl TryFrom<String> for ShowDone {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<ShowDone, String> {
+        s.parse()
+    }
+}
+
+/// Sorts by each key in turn, keeping the fetched order among equal todos.
+/// Todos without a value sort last either way, as in Notion.
+pub fn sort(todos: &mut [Todo], keys: &[SortKey]) {
+    todos.sort_by(|a, b| {
+        keys.iter()
+            .map(
+                |key| match (a.property(&key.property), b.property(&key.property)) {
+                    (Some(a), Some(b)) => {
+                        let ordering = a.compare(&b).unwrap_or(Ordering::Equal);
+                        match key.direction {
+                            Direction::Ascending => ordering,
+                            Direction::Descending => ordering.reverse(),
+                        }
+                    }
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+            )
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+fn compile(input: &str, pattern: &str) -> crate::Result<Regex> {
+    if input.len() > MAX_PATTERN_LEN {
+        return Err(format!("pattern is longer than {MAX_PATTERN_LEN} characters").into());
+    }
+
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("invalid pattern '{input}': {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::{DateRange, PropertyValue};
+
+    fn todo(title: &str, tags: &[&str]) -> Todo {
+        Todo {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Todo::sample(title, false, None)
+        }
+    }
+
+    #[test]
+    fn test_title_regex() {
+        let filter = TodoFilter::new().title_regex("(?i)^pay").unwrap();
+
+        assert!(filter.matches(&todo("Pay rent", &[])));
+        assert!(filter.matches(&todo("pay taxes", &[])));
+        assert!(!filter.matches(&todo("Repay loan", &[])));
+    }
+
+    #[test]
+    fn test_tag_glob() {
+        let filter = TodoFilter::new().tag_glob("proj-*").unwrap();
+
+        assert!(filter.matches(&todo("a", &["home", "proj-notifieru"])));
+        assert!(!filter.matches(&todo("b", &["my-proj-x"])));
+        assert!(!filter.matches(&todo("c", &[])));
+
+        let filter = filter.tag_glob("h?me").unwrap();
+        assert!(filter.matches(&todo("d", &["home", "proj-x"])));
+        assert!(!filter.matches(&todo("e", &["proj-x"])));
+    }
+
+    #[test]
+    fn test_glob_escapes_regex_syntax() {
+        let filter = TodoFilter::new().tag_glob("c++").unwrap();
+
+        assert!(filter.matches(&todo("a", &["c++"])));
+        assert!(!filter.matches(&todo("b", &["cc"])));
+    }
+
+    fn estimated(title: &str, estimate: Option<f64>, due: &str) -> Todo {
+        let mut todo = todo(title, &[]);
+        todo.due = Some(DateRange {
+            start: due.to_owned(),
+            end: None,
+        });
+        if let Some(estimate) = estimate {
+            todo.properties
+                .insert("Estimate".to_owned(), PropertyValue::Number(estimate));
+        }
+        todo
+    }
+
+    #[test]
+    fn test_conditions() {
+        let small = estimated("Small", Some(2.0), "2024-05-01");
+        let large = estimated("Large", Some(13.0), "2024-05-01T09:00:00Z");
+        let unknown = estimated("Unknown", None, "2024-06-01");
+
+        // Numbers compare as numbers, not as text
+        let filter = TodoFilter::new().condition("Estimate >= 3".parse().unwrap());
+        assert!(!filter.matches(&small));
+        assert!(filter.matches(&large));
+        assert!(!filter.matches(&unknown));
+
+        let filter = TodoFilter::new().condition("due<2024-05-02".parse().unwrap());
+        assert!(filter.matches(&small));
+        assert!(filter.matches(&large));
+        assert!(!filter.matches(&unknown));
+
+        let filter = TodoFilter::new().condition("done!=true".parse().unwrap());
+        assert!(filter.matches(&small));
+
+        // Values that aren't of the property's type never match
+        let filter = TodoFilter::new().condition("Estimate=lots".parse().unwrap());
+        assert!(!filter.matches(&large));
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        assert_eq!(
+            "Estimate>=3".parse::<Condition>(),
+            Ok(Condition {
+                property: "Estimate".to_owned(),
+                op: Op::Ge,
+                value: "3".to_owned(),
+            })
+        );
+        assert!("Estimate".parse::<Condition>().is_err());
+        assert!("=3".parse::<Condition>().is_err());
+        assert!("Estimate!3".parse::<Condition>().is_err());
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut todos = vec![
+            estimated("Unknown", None, "2024-05-01"),
+            estimated("Small", Some(2.0), "2024-05-02"),
+            estimated("Large", Some(13.0), "2024-05-02"),
+        ];
+        let titles = |todos: &[Todo]| todos.iter().map(|t| t.title.clone()).collect::<Vec<_>>();
+
+        sort(&mut todos, &["Estimate:desc".parse().unwrap()]);
+        assert_eq!(titles(&todos), ["Large", "Small", "Unknown"]);
+
+        sort(
+            &mut todos,
+            &["due:desc".parse().unwrap(), "Estimate".parse().unwrap()],
+        );
+        assert_eq!(titles(&todos), ["Small", "Large", "Unknown"]);
+
+        assert!("Estimate:down".parse::<SortKey>().is_err());
+    }
+
+    #[test]
+    fn test_parse_show_done() {
+        assert_eq!("never".parse(), Ok(ShowDone::Never));
+        assert_eq!("last".parse(), Ok(ShowDone::Last));
+        assert_eq!(
+            "7d".parse(),
+            Ok(ShowDone::Within(Duration::from_secs(7 * 24 * 60 * 60)))
+        );
+        assert_eq!(
+            "always".parse::<ShowDone>(),
+            Err(
+                "invalid show_done 'always', expected never, last or a duration such as 7d"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn test_pattern_limits() {
+        assert!(TodoFilter::new().title_regex("(unclosed").is_err());
+        assert!(TodoFilter::new().title_regex(&"a".repeat(300)).is_err());
+        assert!(TodoFilter::new().title_regex("\\w{1000}{1000}").is_err());
+    }
+}
@@ -0,0 +1,23 @@
+pub mod alert;
+pub mod build_info;
+pub mod cache;
+pub mod config;
+pub mod datetime;
+pub mod diff;
+pub mod error;
+pub mod events;
+pub mod filter;
+pub mod notify;
+pub mod notion;
+pub mod number;
+pub mod render;
+pub mod review;
+pub mod secrets;
+pub mod snapshot;
+pub mod stats;
+pub mod suggest;
+pub mod todo;
+
+pub use error::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
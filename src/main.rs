@@ -4,100 +4,1233 @@
 //! synchronization across different devices.
 //!
 //! To connect any Notion database with notifieru, you should provide `.secrets`
-//! file in the project root directory. The format of `.secrets` file content
-//! should follow the below specification (the order of the lines may vary):
+//! file in the working directory, or point `--secrets-path` at one elsewhere.
+//! The format of `.secrets` file content should follow the below specification
+//! (the order of the lines may vary):
 //!
 //! ```
 //! DB_URL=<database_url>
 //! API_KEY=<api_key>
 //! ```
+//!
+//! `DB_URL` may be the database's link from Notion's "Copy link", its ID, or
+//! the `https://api.notion.com/v1/databases/<id>/query` endpoint itself.
+//!
+//! `.env` syntax works too: comments, blank lines, `export` and quoted values
+//! are understood, and other keys are left alone, so a project's `.env` can
+//! double as the secrets file.
+//!
+//! `NOTIFIERU_DB_URL` and `NOTIFIERU_API_KEY` environment variables take
+//! precedence over the file, which isn't needed when both are set, and
+//! `--api-key-stdin` or `--api-key-file` over all of them. Builds with
+//! the `keyring` feature can keep the API key in the OS keychain instead, see
+//! `notifieru config set-key`, and builds with the `age` feature can read an
+//! age-encrypted `.secrets`, decrypted with the identity file
+//! `NOTIFIERU_AGE_IDENTITY` (or `age_identity` in the config) points at.
+//!
+//! Everything else, e.g. differently named properties, goes in
+//! `$XDG_CONFIG_HOME/notifieru/config.toml` (`~/.config/notifieru/config.toml`
+//! by default), which may also hold the database URL and the secrets path, or
+//! several of them as profiles to pick with `--profile`.
+//!
+//! ## Exit status
+//!
+//! - `0`: success
+//! - `1`: any other error, e.g. an invalid argument
+//! - `2`: the todos exceeded a `--max-open`, `--max-overdue` or
+//!   `--max-overdue-age` threshold, so CI jobs can gate on e.g. an empty
+//!   "release blockers" database and `notify` can be monitored like an SLO
+//! - `3`: the config file can't be read or is invalid
+//! - `4`: the secrets are missing or malformed
+//! - `5`: a request to Notion failed or timed out, or its response was
+//!   unreadable, which retrying later may fix
+//! - `6`: Notion returned an error, e.g. for a revoked API key or a database
+//!   not shared with the integration
+//! - `7`: the database lacks the properties notifieru reads, as `doctor`
+//!   found
 
-mod secrets;
+mod cli;
 
-use secrets::Secret;
+use cli::{CacheCommand, Cli, Command, ConfigCommand, GlobalArgs, InitArgs, ListArgs, NotifyArgs};
 
-use minreq;
-use serde_json::{json, Value};
+use notifieru::build_info;
+use notifieru::cache::{self, Cache};
+use notifieru::config::{self, Config};
+use notifieru::datetime;
+use notifieru::diff::Diff;
+use notifieru::events::Event;
+use notifieru::filter::{self, Condition, ShowDone, TodoFilter};
+use notifieru::notify::{self, Reminder};
+use notifieru::notion::{self, Client, Filter, QueryBuilder, Schema};
+use notifieru::number::{self, NumberFormat};
+use notifieru::render::{self, Format, TableOptions};
+use notifieru::review::{Period, Review};
+use notifieru::secrets::{self, Secret};
+use notifieru::snapshot::Snapshot;
+use notifieru::stats::{self, Stats};
+use notifieru::suggest;
+use notifieru::todo::{self, PropertyNames, PropertyValue, Todo};
+use notifieru::Error;
 
+use chrono::{Local, TimeDelta};
+use clap::Parser;
+use serde_json::Value;
+use terminal_size::Width;
+
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
-use std::path::PathBuf;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process;
 
-pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+/// Title of the notification raised when a threshold is breached, distinct
+/// from the per-todo reminders so it stands out
+const ALERT_SUMMARY: &str = "notifieru alert";
 
-fn main() -> crate::Result<()> {
-    let _args: Vec<_> = env::args().collect();
+/// Clock skew beyond this makes due soon and overdue checks unreliable
+const MAX_CLOCK_SKEW: TimeDelta = TimeDelta::minutes(1);
 
-    let secret_path = PathBuf::from(".secrets");
-    let Secret { db_url, api_key } = secrets::read_secrets(&secret_path)?;
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {e}");
+        process::exit(e.exit_code());
+    }
+}
 
-    let res = minreq::post(&db_url)
-        .with_header("Authorization", format!("Bearer {api_key}"))
-        .with_header("Notion-Version", "2022-06-28")
-        .with_header("Content-Type", "application/json")
-        .with_json(&json!({"sorts": [{"property": "Due", "direction": "ascending"}]}))?
-        .send()?;
+fn run() -> notifieru::Result<()> {
+    let mut cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Usage errors exit with 1 like any other error, keeping 2 for
+        // exceeded thresholds; help and --version still exit successfully
+        Err(e) if e.use_stderr() => {
+            let _ = e.print();
+            process::exit(1);
+        }
+        Err(e) => e.exit(),
+    };
+    // Commands that don't read the configuration skip loading it, keeping
+    // them fast and working while the configuration is broken
+    match &cli.command {
+        Some(Command::Cache { command }) => {
+            return run_cache(command.clone().unwrap_or(CacheCommand::Status));
+        }
+        Some(Command::Version { verbose }) => {
+            // The configured Notion-Version is reported when the configuration
+            // can be read, and left at the default otherwise
+            if *verbose {
+                let config = match &cli.global.config {
+                    Some(path) => Config::load(path),
+                    None => Config::load_default(),
+                };
+                cli.global.loaded_config = config.unwrap_or_default();
+            }
+            print!(
+                "{}",
+                build_info::report(*verbose, cli.global.notion_version())
+            );
+            return Ok(());
+        }
+        _ => {}
+    }
 
-    process_todos(res)
-}
+    let config = match &cli.global.config {
+        Some(path) => Config::load(path)?,
+        None => Config::load_default()?,
+    };
+    cli.global.loaded_config = config.with_profile(cli.global.profile.as_deref())?;
+    for (field, name) in &cli.global.properties {
+        cli.global
+            .loaded_config
+            .properties
+            .set(field, name)
+            .map_err(Error::Config)?;
+    }
+    let list_args = match &cli.command {
+        None => Some(&cli.list),
+        Some(Command::List(args)) => Some(&**args),
+        _ => None,
+    };
+    if let Some(args) = list_args.filter(|args| !args.show_properties.is_empty()) {
+        let columns = &mut cli.global.loaded_config.output.columns;
+        if columns.is_empty() {
+            columns.extend(render::DEFAULT_COLUMNS.map(str::to_owned));
+        }
+        columns.extend(args.show_properties.iter().cloned());
+    }
+    if let Some(args) = list_args {
+        // Properties filtered or sorted by are read like extra columns
+        let used = args
+            .conditions
+            .iter()
+            .map(Condition::property)
+            .chain(args.sort.iter().map(|key| key.property.as_str()));
+        let extra = &mut cli.global.loaded_config.properties.extra;
+        for name in used {
+            if !todo::FIELDS.contains(&name) && !extra.iter().any(|known| known == name) {
+                extra.push(name.to_owned());
+            }
+        }
+    }
+    let global = &cli.global;
+    if global.events
+        && !matches!(
+            cli.command,
+            None | Some(Command::List(_) | Command::Notify(_))
+        )
+    {
+        return Err("--events only applies to list and notify".into());
+    }
 
-fn process_todos(res: minreq::Response) -> crate::Result<()> {
-    let json = res.json::<Value>()?;
-    let todos = json["results"]
-        .as_array()
-        .ok_or("expected 'results' array field which is not present in the response")?;
+    match cli.command {
+        None => run_list(global, &cli.list),
+        Some(Command::List(args)) => run_list(global, &args),
+        Some(Command::Notify(args)) => run_notify(global, &args),
+        Some(Command::Config { command }) => match command.unwrap_or(ConfigCommand::Show) {
+            ConfigCommand::Show => run_config(global),
+            ConfigCommand::SetKey => run_set_key(),
+        },
+        Some(Command::Doctor) => run_doctor(global),
+        Some(Command::Init(args)) => run_init(global, &args),
+        Some(Command::Diff(args)) => run_diff(global, args.against.as_deref()),
+        Some(Command::Review(args)) => {
+            let period = if args.day { Period::Day } else { Period::Week };
+            run_review(global, period)
+        }
+        Some(Command::Stats(args)) => run_stats(global, args.publish.as_deref()),
+        Some(Command::Prompt) => run_prompt(global),
+        Some(Command::Cache { .. } | Command::Version { .. }) => {
+            unreachable!("handled before loading the configuration")
+        }
+    }
+}
 
-    let mut errors: Vec<String> = Vec::new();
+fn run_list(global: &GlobalArgs, args: &ListArgs) -> notifieru::Result<()> {
+    let query = args.query.as_deref();
+    let format = match args.format.or(global.loaded_config.output.format) {
+        Some(format) => format,
+        None if query.is_some() => Format::Json,
+        None => Format::default(),
+    };
+    if query.is_some() && format != Format::Json {
+        return Err("--query only applies to --format json".into());
+    }
+    let mut filter = TodoFilter::new();
+    if let Some(pattern) = &args.title {
+        filter = filter.title_regex(pattern)?;
+    }
+    for glob in &args.tags {
+        filter = filter.tag_glob(glob)?;
+    }
+    for condition in &args.conditions {
+        filter = filter.condition(condition.clone());
+    }
 
-    for (i, todo) in todos.iter().enumerate() {
-        let properties = &todo["properties"];
+    let output = &global.loaded_config.output;
+    let show_done = match args.show_done.or(output.show_done) {
+        _ if args.all => None,
+        Some(ShowDone::Never) | None => None,
+        show_done => show_done,
+    };
+    let done_since = match show_done {
+        Some(ShowDone::Within(within)) => {
+            let since = Local::now() - TimeDelta::from_std(within).map_err(|e| e.to_string())?;
+            Some(since.format("%Y-%m-%dT%H:%M:%S%:z").to_string())
+        }
+        _ => None,
+    };
 
-        let title = match properties["Name"]["title"][0]["plain_text"].as_str() {
-            Some(t) => t,
-            None => {
-                errors.push(format!("todo {i}: missing or invalid title"));
+    let (client, database_id) = connect(global)?;
+    let open_only = !args.all && show_done != Some(ShowDone::Last);
+    // Notion filters statuses and selects differently, which only the schema
+    // tells apart
+    let by_status = open_only && !global.loaded_config.done_when.status.is_empty();
+    let schema = match by_status || !args.statuses.is_empty() {
+        true => Some(Schema::fetch(&client, &database_id)?),
+        false => None,
+    };
+    if let Some(schema) = schema.as_ref().filter(|_| !args.statuses.is_empty()) {
+        let name = &global.loaded_config.properties.status;
+        if !matches!(schema.kind(name), Some("status" | "select")) {
+            return Err(Error::Schema(format!(
+                "--status needs '{name}' to be a status or select property; point properties.status in the config file at one"
+            )));
+        }
+        // Notion rejects a filter on an option the property doesn't have
+        let options = schema.options(name);
+        for status in &args.statuses {
+            if options.is_empty() || options.contains(status) {
                 continue;
             }
-        };
+            let hint = match suggest::closest(status, options.iter().map(String::as_str)) {
+                Some(option) => format!("did you mean '{option}'?"),
+                None => format!("expected one of {}", options.join(", ")),
+            };
+            return Err(Error::Config(format!(
+                "'{status}' is not an option of '{name}', {hint}"
+            )));
+        }
+    }
+    let selection = Selection {
+        open_only,
+        done_since: done_since.as_deref(),
+        due_before: args.due_before.as_deref(),
+        due_after: args.due_after.as_deref(),
+        statuses: &args.statuses,
+        schema: schema.as_ref(),
+    };
+    let todos = fetch_todos(global, &client, &database_id, &selection)?;
 
-        let start_date = properties["Due"]["date"]["start"].as_str();
-        let end_date = properties["Due"]["date"]["end"].as_str();
+    // A tag no todo has would just list nothing, though it's likely a typo
+    let known: BTreeSet<&str> = todos
+        .iter()
+        .flat_map(|todo| todo.tags.iter().map(String::as_str))
+        .collect();
+    for tag in &args.tags {
+        if tag.contains(['*', '?']) || known.contains(tag.as_str()) {
+            continue;
+        }
+        if let Some(known) = suggest::closest(tag, known.iter().copied()) {
+            warn(
+                global,
+                &format!("no todo is tagged '{tag}', did you mean '{known}'?"),
+            );
+        }
+    }
 
-        let done = match properties["Done"]["checkbox"].as_bool() {
-            Some(d) => d,
-            None => {
-                errors.push(format!("todo {i}: missing or invalid 'Done' checkbox"));
-                continue;
+    // Everything below renders the one fetch
+    let now = Local::now();
+    let mut shown = filter.apply(todos.clone());
+    filter::sort(&mut shown, &args.sort);
+    if show_done.is_some() {
+        // Stable, so both the open and the done todos keep their order
+        shown.sort_by_key(|todo| todo.done);
+    }
+    let shown = Snapshot::new(shown);
+    let tables = (format == Format::Table && !global.events)
+        || args.exports.iter().any(|(f, _)| *f == Format::Table);
+    let table_options = TableOptions {
+        title_width: args.max_width,
+        wrap: args.wrap,
+        max_rows: args.rows,
+        columns: output.columns.clone(),
+        number_formats: if tables {
+            number_formats(global, &client, &database_id, &output.columns, &shown.todos)
+        } else {
+            BTreeMap::new()
+        },
+        locale: output.locale.unwrap_or_default(),
+    };
+    if global.events {
+        for todo in &shown.todos {
+            emit(&Event::Todo(todo));
+        }
+    } else {
+        print!(
+            "{}",
+            render::listing(
+                format,
+                &shown,
+                query,
+                &fit_terminal(&table_options, &shown.todos),
+                &now
+            )?
+        );
+    }
+    if format == Format::Gha {
+        if let Some(path) = env::var_os("GITHUB_STEP_SUMMARY") {
+            let mut summary = OpenOptions::new().create(true).append(true).open(path)?;
+            summary.write_all(render::gha_summary(&shown.todos, &now).as_bytes())?;
+        }
+    }
+    for (format, path) in &args.exports {
+        let rendered = render::listing(*format, &shown, query, &table_options, &now)?;
+        // Write then rename, so e.g. org-agenda never reads half a file
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, rendered)
+            .and_then(|()| fs::rename(&tmp, path))
+            .map_err(|e| format!("failed to export to '{}': {e}", path.display()))?;
+    }
+    if args.notify {
+        let within = datetime::parse_duration(cli::NOTIFY_WITHIN)?;
+        send_reminders(
+            global,
+            &notify::reminders(&shown.todos, within, &now),
+            false,
+        )?;
+    }
+
+    // Part of the todos would read as the rest removed on the next diff
+    if selection.is_everything() {
+        update_cache(global, &database_id, todos);
+    }
+
+    let breaches = args.thresholds.policy().breaches(&shown.todos, &now);
+    if !breaches.is_empty() {
+        return Err(Error::Threshold(breaches));
+    }
+
+    Ok(())
+}
+
+/// How the number columns shown are formatted, which only the database schema
+/// knows. They are shown plain when it can't be fetched.
+fn number_formats(
+    global: &GlobalArgs,
+    client: &Client,
+    database_id: &str,
+    columns: &[String],
+    todos: &[Todo],
+) -> BTreeMap<String, NumberFormat> {
+    let has_numbers = columns.iter().any(|column| {
+        todos
+            .iter()
+            .any(|todo| matches!(todo.property(column), Some(PropertyValue::Number(_))))
+    });
+    if !has_numbers {
+        return BTreeMap::new();
+    }
+
+    match client.database_properties(database_id) {
+        Ok(properties) => number::formats(&properties),
+        Err(e) => {
+            warn(global, &format!("failed to read number formats: {e}"));
+            BTreeMap::new()
+        }
+    }
+}
+
+/// Narrows the title column when the table would overflow the terminal, unless
+/// `--max-width` picked a width already
+fn fit_terminal(options: &TableOptions, todos: &[Todo]) -> TableOptions {
+    let mut options = options.clone();
+    if options.title_width.is_some() || !io::stdout().is_terminal() {
+        return options;
+    }
+    let Some((Width(columns), _)) = terminal_size::terminal_size() else {
+        return options;
+    };
+
+    let fit = render::fit_title_width(todos, &options, usize::from(columns));
+    let longest = todos.iter().map(|t| t.title.chars().count()).max();
+    if longest.is_some_and(|longest| longest > fit) {
+        options.title_width = Some(fit);
+    }
+    options
+}
+
+/// `notify [--within <duration>] [--dry-run]`, raising a desktop notification
+/// for every open todo that is overdue or due within the given duration. A
+/// breached threshold raises a single alert on top and exits with status 2.
+fn run_notify(global: &GlobalArgs, args: &NotifyArgs) -> notifieru::Result<()> {
+    let (client, database_id) = connect(global)?;
+    let todos = fetch_todos(global, &client, &database_id, &Selection::default())?;
+
+    let now = Local::now();
+    let reminders = notify::reminders(&todos, args.within, &now);
+    if reminders.is_empty() && !global.events {
+        println!(
+            "nothing due within {}",
+            datetime::format_duration(args.within)
+        );
+    }
+    send_reminders(global, &reminders, args.dry_run)?;
+
+    let breaches = args.thresholds.policy().breaches(&todos, &now);
+    update_cache(global, &database_id, todos);
+
+    if !breaches.is_empty() {
+        let body = breaches.join("\n");
+        if !args.dry_run {
+            notify::desktop(ALERT_SUMMARY, &body)?;
+        }
+        if global.events {
+            emit(&Event::NotificationSent {
+                summary: ALERT_SUMMARY,
+                body: &body,
+                dry_run: args.dry_run,
+            });
+        } else if args.dry_run {
+            println!("{ALERT_SUMMARY}: {}", breaches.join(", "));
+        }
+        return Err(Error::Threshold(breaches));
+    }
+    Ok(())
+}
+
+fn send_reminders(
+    global: &GlobalArgs,
+    reminders: &[Reminder],
+    dry_run: bool,
+) -> notifieru::Result<()> {
+    for Reminder { summary, body } in reminders {
+        if !dry_run {
+            notify::desktop(summary, body)?;
+        }
+        if global.events {
+            emit(&Event::NotificationSent {
+                summary,
+                body,
+                dry_run,
+            });
+        } else if dry_run {
+            println!("{summary}: {body}");
+        }
+    }
+    Ok(())
+}
+
+/// Prints a line of the `--events` stream
+fn emit(event: &Event) {
+    println!("{}", event.to_line());
+}
+
+/// Warnings go to stderr, or into the stream with `--events`
+fn warn(global: &GlobalArgs, message: &str) {
+    if global.events {
+        emit(&Event::Warning { message });
+    } else {
+        eprintln!("warning: {message}");
+    }
+}
+
+/// `config`, printing what the flags and the secrets file resolve to
+fn run_config(global: &GlobalArgs) -> notifieru::Result<()> {
+    let Secret {
+        db_url, api_key, ..
+    } = read_secrets(global)?;
+    let (base_url, database_id) = parse_db_url(&db_url)?;
+    let cache_dir = cache::default_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "unavailable".to_owned());
+
+    let config_file = match (&global.config, config::default_path()) {
+        (Some(path), _) => path.display().to_string(),
+        (None, Some(path)) if path.exists() => path.display().to_string(),
+        (None, _) => "none".to_owned(),
+    };
+    println!("config file: {config_file}");
+    println!(
+        "profile: {}",
+        global.loaded_config.profile.as_deref().unwrap_or("none")
+    );
+    println!("secrets file: {}", global.secrets_path().display());
+    println!("database: {database_id}");
+    println!("api base url: {base_url}");
+    println!("api key: {}", mask(&api_key));
+    println!("notion version: {}", global.notion_version());
+    println!("cache directory: {cache_dir}");
+    Ok(())
+}
+
+/// `config set-key`, reading the key from stdin so it stays out of the shell
+/// history
+#[cfg(feature = "keyring")]
+fn run_set_key() -> notifieru::Result<()> {
+    eprint!("Notion API key: ");
+    let mut api_key = String::new();
+    std::io::stdin().read_line(&mut api_key)?;
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err("no API key given".into());
+    }
+
+    secrets::keyring::set_api_key(api_key)?;
+    eprintln!("stored the API key in the keychain");
+    Ok(())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn run_set_key() -> notifieru::Result<()> {
+    Err("this build has no keychain support, rebuild with the keyring feature".into())
+}
+
+/// `doctor`, running every check even after a failure so a single run shows
+/// everything that needs fixing
+fn run_doctor(global: &GlobalArgs) -> notifieru::Result<()> {
+    let mut failed = 0;
+    let mut schema_fits = true;
+    let mut check = |name: &str, result: notifieru::Result<String>| match result {
+        Ok(detail) => {
+            println!("ok   {name}: {detail}");
+            true
+        }
+        Err(e) => {
+            println!("FAIL {name}: {e}");
+            failed += 1;
+            false
+        }
+    };
+
+    let connected = match connect(global) {
+        Ok(connected) => {
+            check("secrets", Ok("DB_URL and API_KEY found".to_owned()));
+            Some(connected)
+        }
+        Err(e) => {
+            check("secrets", Err(e));
+            None
+        }
+    };
+
+    if let Some((client, database_id)) = connected {
+        check(
+            "api key",
+            client.me().map(|me| {
+                format!(
+                    "authenticated as {}",
+                    me["name"].as_str().unwrap_or("an unnamed integration")
+                )
+            }),
+        );
+        let readable = check(
+            "database",
+            client
+                .query_database(&database_id, &QueryBuilder::new().page_size(1).build())
+                .map(|_| format!("{database_id} is readable")),
+        );
+        if readable {
+            let names = global.loaded_config.property_names();
+            schema_fits = check(
+                "schema",
+                client
+                    .database_properties(&database_id)
+                    .and_then(
+                        |properties| match names.check_schema(&properties).as_slice() {
+                            [] => Ok(format!(
+                                "'{}', '{}' and '{}' found",
+                                names.title, names.done, names.due
+                            )),
+                            problems => Err(problems.join("\n     ").into()),
+                        },
+                    ),
+            );
+        }
+        check("clock", check_clock(&client));
+    }
+
+    let message = match failed {
+        0 => return Ok(()),
+        1 => "1 check failed".to_owned(),
+        n => format!("{n} checks failed"),
+    };
+    Err(match schema_fits {
+        true => message.into(),
+        false => Error::Schema(message),
+    })
+}
+
+/// `init`, asking for the integration token and the database, checking the
+/// database can be queried, asking which status options mean done if it has no
+/// checkbox, and then writing the secrets file, and a config file pointing at
+/// it if there is none yet
+fn run_init(global: &GlobalArgs, args: &InitArgs) -> notifieru::Result<()> {
+    let secrets_path = std::path::absolute(global.secrets_path())?;
+    if secrets_path.exists() && !args.force {
+        return Err(format!(
+            "'{}' exists, pass --force to overwrite it",
+            secrets_path.display()
+        )
+        .into());
+    }
+
+    let mut input = io::stdin().lock().lines();
+    let mut ask = |question: &str| -> notifieru::Result<String> {
+        print!("{question}: ");
+        io::stdout().flush()?;
+        match input.next() {
+            Some(line) => Ok(line?.trim().to_owned()),
+            None => Err(format!("no answer to '{question}'").into()),
+        }
+    };
+
+    println!("Create an integration at https://www.notion.so/my-integrations and share the database with it.");
+    let api_key = ask("Integration token")?;
+    if api_key.is_empty() {
+        return Err("the integration token is required".into());
+    }
+    let client = client(global, api_key.clone(), &args.api_base_url)?;
+
+    let databases = search_databases(&client).unwrap_or_else(|e| {
+        eprintln!("warning: failed to list databases: {e}");
+        Vec::new()
+    });
+    for (i, (id, title)) in databases.iter().enumerate() {
+        println!("{:3}. {title} ({id})", i + 1);
+    }
+    let answer = if databases.is_empty() {
+        ask("Database ID or URL")?
+    } else {
+        ask("Database (number, ID or URL)")?
+    };
+    let database_id = match answer.parse::<usize>() {
+        Ok(n) if (1..=databases.len()).contains(&n) => databases[n - 1].0.clone(),
+        _ if answer.is_empty() => return Err("the database is required".into()),
+        _ => notion::parse_database(&answer)?.1.to_owned(),
+    };
+
+    client
+        .query_database(&database_id, &QueryBuilder::new().page_size(1).build())
+        .map_err(|e| format!("can't query database '{database_id}': {e}"))?;
+
+    // Without a checkbox, done is read from a status (or select), and which of
+    // its options mean done is asked from the ones the schema lists
+    let schema = Schema::fetch(&client, &database_id).unwrap_or_else(|e| {
+        eprintln!("warning: failed to read the database schema: {e}");
+        Schema::default()
+    });
+    let status = match schema.of_kind("checkbox").next() {
+        Some(_) => None,
+        None => schema
+            .of_kind("status")
+            .chain(schema.of_kind("select"))
+            .find(|name| !schema.options(name).is_empty()),
+    };
+    let mut done_when = None;
+    if let Some(name) = status {
+        let options = schema.options(name);
+        println!("'{name}' tells whether a todo is done, with the options:");
+        for (i, option) in options.iter().enumerate() {
+            println!("{:3}. {option}", i + 1);
+        }
+        let answer = ask("Options meaning done (numbers or names, comma-separated)")?;
+        done_when = Some((name, pick_options(name, options, &answer)?));
+    }
+
+    let db_url = format!(
+        "{}/databases/{database_id}/query",
+        args.api_base_url.trim_end_matches('/')
+    );
+    write_private(
+        &secrets_path,
+        &format!("DB_URL={db_url}\nAPI_KEY={api_key}\n"),
+    )?;
+    println!("wrote {}", secrets_path.display());
+
+    match global.config.clone().or_else(config::default_path) {
+        Some(path) if !path.exists() => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            // A JSON string is a valid TOML one
+            let secrets = serde_json::to_string(&secrets_path.display().to_string())?;
+            fs::write(
+                &path,
+                format!("secrets_path = {secrets}\n{}", done_when_toml(&done_when)?),
+            )?;
+            println!("wrote {}", path.display());
+        }
+        Some(path) => {
+            println!("left {} as it is", path.display());
+            if done_when.is_some() {
+                print!("add to it:\n{}", done_when_toml(&done_when)?);
+            }
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// The options `answer` picks by number or name, e.g. `1, Done`
+fn pick_options(name: &str, options: &[String], answer: &str) -> notifieru::Result<Vec<String>> {
+    let picked: Vec<_> = answer
+        .split(',')
+        .map(str::trim)
+        .filter(|pick| !pick.is_empty())
+        .map(|pick| match pick.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => Ok(options[n - 1].clone()),
+            _ if options.iter().any(|option| option == pick) => Ok(pick.to_owned()),
+            _ => {
+                let hint = match suggest::closest(pick, options.iter().map(String::as_str)) {
+                    Some(option) => format!("did you mean '{option}'?"),
+                    None => format!("expected one of {}", options.join(", ")),
+                };
+                Err(format!("'{pick}' is not an option of '{name}', {hint}"))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    if picked.is_empty() {
+        return Err(format!("pick the options of '{name}' that mean done").into());
+    }
+    Ok(picked)
+}
+
+/// The config file lines reading done from the status property `done_when`
+/// names, if any
+fn done_when_toml(done_when: &Option<(&str, Vec<String>)>) -> notifieru::Result<String> {
+    let Some((name, options)) = done_when else {
+        return Ok(String::new());
+    };
+    // JSON strings and arrays of them are valid TOML ones
+    Ok(format!(
+        "done_when = {{ status = {} }}\n\n[properties]\ndone = {}\n",
+        serde_json::to_string(options)?,
+        serde_json::to_string(name)?
+    ))
+}
+
+/// The databases the integration can see, as ids and titles
+fn search_databases(client: &Client) -> notifieru::Result<Vec<(String, String)>> {
+    // Since 2025-09-03 search finds data sources, which belong to a database
+    let data_sources = client.version() >= notion::ApiVersion::V2025_09_03;
+    let object = if data_sources {
+        "data_source"
+    } else {
+        "database"
+    };
+    let list = client.search(&serde_json::json!({
+        "filter": {"property": "object", "value": object}
+    }))?;
+
+    Ok(list
+        .results
+        .iter()
+        .filter_map(|result| {
+            let id = if data_sources {
+                result["parent"]["database_id"].as_str()?
+            } else {
+                result["id"].as_str()?
+            };
+            let title: String = result["title"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|part| part["plain_text"].as_str())
+                .collect();
+            Some((id.to_owned(), title))
+        })
+        .collect())
+}
+
+/// Writes a file only the current user can read, as it holds the API key
+fn write_private(path: &Path, content: &str) -> notifieru::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("failed to write '{}': {e}", path.display()))?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+/// `diff [--against <snapshot.json>]`, comparing a fresh fetch with the given
+/// snapshot or, by default, with the cached result of the previous run
+fn run_diff(global: &GlobalArgs, against: Option<&Path>) -> notifieru::Result<()> {
+    let (client, database_id) = connect(global)?;
+
+    let before = match against {
+        Some(path) => Snapshot::load(path)?,
+        None => Cache::open_default()?.load(&database_id)?.ok_or(
+            "no cached listing to compare against yet, run notifieru once or pass --against",
+        )?,
+    };
+    let todos = fetch_todos(global, &client, &database_id, &Selection::default())?;
+
+    print!("{}", render::diff(&Diff::between(&before.todos, &todos)));
+
+    update_cache(global, &database_id, todos);
+    Ok(())
+}
+
+/// `review [--day|--week]`, printing a markdown report
+fn run_review(global: &GlobalArgs, period: Period) -> notifieru::Result<()> {
+    let (client, database_id) = connect(global)?;
+    let todos = fetch_todos(global, &client, &database_id, &Selection::default())?;
+
+    let review = Review::new(&todos, period, &Local::now());
+    print!("{}", render::review_markdown(&review));
+
+    update_cache(global, &database_id, todos);
+    Ok(())
+}
+
+/// `stats [--publish <page-id>]`, printing a summary and optionally replacing
+/// the one published on a Notion page
+fn run_stats(global: &GlobalArgs, publish: Option<&str>) -> notifieru::Result<()> {
+    let (client, database_id) = connect(global)?;
+    let todos = fetch_todos(global, &client, &database_id, &Selection::default())?;
+
+    let now = Local::now();
+    let stats = Stats::new(&todos, &now);
+    println!("{}", stats.summary());
+
+    if let Some(page_id) = publish {
+        // Listed in full before deleting any, which would shift the cursor
+        let mut published = Vec::new();
+        let mut cursor = None;
+        loop {
+            let children = client.block_children(page_id, cursor.as_deref())?;
+            published.extend(
+                children
+                    .results
+                    .iter()
+                    .filter(|b| stats::is_published_block(b))
+                    .filter_map(|block| block["id"].as_str().map(str::to_owned)),
+            );
+            match children.next_cursor {
+                Some(next) if children.has_more => cursor = Some(next),
+                _ => break,
+            }
+        }
+        let deleted = notion::fetch_bounded(&published, notion::DEFAULT_CONCURRENCY, |id| {
+            client.delete_block(id)
+        });
+        for result in deleted {
+            result?;
+        }
+
+        let blocks = stats.to_blocks(&now.format("%Y-%m-%d %H:%M").to_string());
+        client.append_block_children(page_id, &blocks)?;
+        println!("published to page {page_id}");
+    }
+
+    update_cache(global, &database_id, todos);
+    Ok(())
+}
+
+/// Builds the Notion client from the global flags and the secrets file,
+/// returning it with the id of the configured database
+fn connect(global: &GlobalArgs) -> notifieru::Result<(Client, String)> {
+    let Secret {
+        db_url, api_key, ..
+    } = read_secrets(global)?;
+    let (base_url, database_id) = parse_db_url(&db_url)?;
+    Ok((client(global, api_key, base_url)?, database_id.to_owned()))
+}
+
+fn client(global: &GlobalArgs, api_key: String, base_url: &str) -> notifieru::Result<Client> {
+    let mut client = Client::new(api_key)
+        .with_base_url(base_url)
+        .with_version(global.notion_version())
+        .with_fault(global.inject_fault);
+    if let Some(max) = global.loaded_config.max_response_bytes {
+        client = client.with_max_response_bytes(max);
+    }
+    if let Some(timeout) = global.timeout.or(global.loaded_config.timeout) {
+        client = client.with_timeout(timeout);
+    }
+    if let Some(max) = global.loaded_config.max_attempts {
+        client = client.with_max_attempts(max);
+    }
+    let config = &global.loaded_config;
+    if let Some(user_agent) = global.user_agent.as_ref().or(config.user_agent.as_ref()) {
+        client = client.with_user_agent(user_agent.clone());
+    }
+    let flagged = |name: &str| {
+        global
+            .headers
+            .iter()
+            .any(|(flag, _)| flag.eq_ignore_ascii_case(name))
+    };
+    let configured = config.headers.iter().filter(|(name, _)| !flagged(name));
+    for (name, value) in configured.chain(global.headers.iter().map(|(name, value)| (name, value)))
+    {
+        client = client.with_header(name.clone(), value.clone());
+    }
+    let configured = global.loaded_config.proxy.as_deref();
+    match notion::proxy_for(base_url, configured, |name| env::var(name).ok()) {
+        Some(proxy) => client = client.with_proxy(&proxy)?,
+        // minreq reads these by itself when no proxy is set, heedless of
+        // NO_PROXY. No other thread runs yet to read the environment.
+        None => {
+            for name in ["http_proxy", "https_proxy", "all_proxy"] {
+                env::remove_var(name);
+            }
+        }
+    }
+    Ok(client)
+}
+
+fn read_secrets(global: &GlobalArgs) -> notifieru::Result<Secret> {
+    secrets::read_secrets_with(
+        global.secrets_path(),
+        &global.loaded_config,
+        global.api_key_source().as_ref(),
+    )
+}
+
+fn parse_db_url(db_url: &str) -> notifieru::Result<(&str, &str)> {
+    Ok(notion::parse_database(db_url)?)
+}
+
+/// Keeps just enough of the key to tell integrations apart
+fn mask(api_key: &str) -> String {
+    let shown: String = api_key.chars().take(4).collect();
+    format!("{shown}****")
+}
+
+fn fetch_todos(
+    global: &GlobalArgs,
+    client: &Client,
+    database_id: &str,
+    selection: &Selection,
+) -> notifieru::Result<Vec<Todo>> {
+    let config = &global.loaded_config;
+    let max_pages = config.max_pages.unwrap_or(notion::DEFAULT_MAX_PAGES).max(1);
+    let query = |sort: &str, names: &PropertyNames| {
+        let mut query = QueryBuilder::new().sort(sort, config.sort.direction);
+        if let Some(filter) = selection.filter(names) {
+            query = query.filter(filter);
+        }
+        client.query_database_pages(database_id, &query, max_pages)
+    };
+    if global.events {
+        emit(&Event::FetchStarted { database_id });
+    }
+    let mut names = config.property_names();
+    let mut result = query(config.sort_property(), &names);
+
+    // Without a mapping, a database whose properties are named differently is
+    // found out by a failing sort or pages lacking them, and its schema tells
+    // which properties to read instead
+    let fits = |list: &notion::List| {
+        list.results.first().is_none_or(|page| {
+            let properties = &page["properties"];
+            properties.get(&names.title).is_some() && properties.get(&names.done).is_some()
+        })
+    };
+    if config.properties.maps_defaults() && !result.as_ref().is_ok_and(fits) {
+        if let Ok(schema) = Schema::fetch(client, database_id) {
+            let detected = schema.detect(&names);
+            if detected != names {
+                warn(
+                    global,
+                    &format!(
+                        "reading '{}', '{}' and '{}', set [properties] in the config file to pick others",
+                        detected.title, detected.due, detected.done
+                    ),
+                );
+                let sort = config.sort.property.as_deref().unwrap_or(&detected.due);
+                result = query(sort, &detected);
+                names = detected;
             }
-        };
+        }
+    }
 
-        let mut output = format!("[{}] {}: {:35} | ", if done { "x" } else { " " }, i, title);
+    let list = result?;
+    if list.has_more {
+        warn(
+            global,
+            &format!("listing only the first {max_pages} pages of todos, raise max_pages in the config file to list them all"),
+        );
+    }
+    if let Err(e) = check_clock(client) {
+        warn(global, &e.to_string());
+    }
+    let todos = process_todos(global, &list.results, &names);
+    if global.events {
+        emit(&Event::FetchCompleted {
+            database_id,
+            count: todos.len(),
+        });
+    }
+    Ok(todos)
+}
+
+/// Which todos a listing asks Notion for
+#[derive(Debug, Clone, Default)]
+struct Selection<'a> {
+    open_only: bool,
+    /// With `open_only`, also todos completed since then, by their
+    /// `completed` property
+    done_since: Option<&'a str>,
+    due_before: Option<&'a str>,
+    due_after: Option<&'a str>,
+    /// Options of the `status` property any of which todos must have
+    statuses: &'a [String],
+    /// Tells status properties from selects, which Notion filters differently
+    schema: Option<&'a Schema>,
+}
+
+impl Selection<'_> {
+    fn is_everything(&self) -> bool {
+        !self.open_only
+            && self.due_before.is_none()
+            && self.due_after.is_none()
+            && self.statuses.is_empty()
+    }
+
+    /// Todos whose property `name`, a status or select, is `option`, or isn't
+    fn option(&self, name: &str, option: &str, equals: bool) -> Filter {
+        let is_select = self.schema.and_then(|schema| schema.kind(name)) == Some("select");
+        match (is_select, equals) {
+            (true, true) => Filter::select(name).equals(option),
+            (true, false) => Filter::select(name).does_not_equal(option),
+            (false, true) => Filter::status(name).equals(option),
+            (false, false) => Filter::status(name).does_not_equal(option),
+        }
+    }
 
-        if let Some(start) = start_date {
-            push_datetime(start, &mut output);
+    /// The query filter over the properties `names` reads, if any
+    fn filter(&self, names: &PropertyNames) -> Option<Filter> {
+        let mut filters = Vec::new();
+        let mut choices = Vec::new();
+        if self.open_only {
+            let statuses = &names.done_when.status;
+            let mut open = Vec::new();
+            if statuses.is_empty() {
+                open.push(Filter::checkbox(&names.done).equals(false));
+            }
+            for status in statuses {
+                open.push(self.option(&names.done, status, false));
+            }
+            match self.done_since {
+                Some(date) => choices.push(vec![
+                    open,
+                    vec![Filter::date(&names.completed).on_or_after(date)],
+                ]),
+                None => filters.extend(open),
+            }
+        }
+        if let Some(date) = self.due_before {
+            filters.push(Filter::date(&names.due).before(date));
+        }
+        if let Some(date) = self.due_after {
+            filters.push(Filter::date(&names.due).after(date));
         }
-        if let Some(end) = end_date {
-            output.push_str(&format!(" ~ "));
-            push_datetime(end, &mut output);
+        match self.statuses {
+            [] => {}
+            [status] => filters.push(self.option(&names.status, status, true)),
+            statuses => choices.push(
+                statuses
+                    .iter()
+                    .map(|option| vec![self.option(&names.status, option, true)])
+                    .collect(),
+            ),
         }
+        combine(filters, choices)
+    }
+}
+
+/// A filter requiring all of `filters` and, of each of `choices`, all filters
+/// of one of its alternatives, if there are any. Notion only accepts compound
+/// filters two levels deep, so unless a single choice fits into the `and` as an
+/// `or` of plain conditions, every combination of alternatives becomes a branch
+/// of one `or` instead.
+fn combine(mut filters: Vec<Filter>, choices: Vec<Vec<Vec<Filter>>>) -> Option<Filter> {
+    match choices.as_slice() {
+        [] => all_of(filters),
+        [choice] if choice.iter().all(|alternative| alternative.len() == 1) => {
+            filters.push(Filter::or(choice.concat()));
+            all_of(filters)
+        }
+        _ => {
+            let mut branches = vec![filters];
+            for choice in &choices {
+                branches = branches
+                    .iter()
+                    .flat_map(|branch| {
+                        choice
+                            .iter()
+                            .map(move |alternative| [&branch[..], alternative].concat())
+                    })
+                    .collect();
+            }
+            Some(Filter::or(
+                branches.into_iter().filter_map(all_of).collect(),
+            ))
+        }
+    }
+}
+
+/// A filter requiring all of `filters`, if there are any
+fn all_of(mut filters: Vec<Filter>) -> Option<Filter> {
+    match filters.len() {
+        0 => None,
+        1 => filters.pop(),
+        _ => Some(Filter::and(filters)),
+    }
+}
+
+/// Compares the local clock with the one Notion reported on the latest
+/// response
+fn check_clock(client: &Client) -> notifieru::Result<String> {
+    let Some(skew) = client.clock_skew() else {
+        return Ok("the API reported no time to compare with".to_owned());
+    };
+    let (direction, by) = match skew.to_std() {
+        Ok(ahead) => ("ahead of", ahead),
+        Err(_) => ("behind", skew.abs().to_std().map_err(|e| e.to_string())?),
+    };
+
+    if skew.abs() > MAX_CLOCK_SKEW {
+        Err(format!(
+            "the local clock is {} {direction} Notion's, due and overdue times may be off",
+            datetime::format_duration(by)
+        )
+        .into())
+    } else {
+        Ok("in sync with Notion".to_owned())
+    }
+}
+
+/// The cache always holds the full, unfiltered listing. It is an optimization,
+/// so failing to write it must not fail the run.
+fn update_cache(global: &GlobalArgs, database_id: &str, todos: Vec<Todo>) {
+    let snapshot = Snapshot::new(todos);
+    if let Err(e) = Cache::open_default().and_then(|cache| cache.store(database_id, &snapshot)) {
+        warn(global, &format!("failed to update the cache: {e}"));
+    }
+}
 
-        println!("{output}");
+/// `prompt`, from the cache alone so shell prompts never wait on Notion.
+/// Prints nothing until a full listing was cached.
+fn run_prompt(global: &GlobalArgs) -> notifieru::Result<()> {
+    let Secret { db_url, .. } = read_secrets(global)?;
+    let (_, database_id) = parse_db_url(&db_url)?;
+    if let Some(snapshot) = Cache::open_default()?.load(database_id)? {
+        print!("{}", render::prompt(&snapshot.todos, &Local::now()));
     }
+    Ok(())
+}
+
+/// `cache status|clear|gc [--max-age <duration>]`
+fn run_cache(command: CacheCommand) -> notifieru::Result<()> {
+    let cache = Cache::open_default()?;
 
-    if !errors.is_empty() {
-        eprintln!("Errors encountered while processing todos:");
-        for error in errors {
-            eprintln!("{}", error);
+    match command {
+        CacheCommand::Status => {
+            let entries = cache.entries()?;
+            println!("cache directory: {}", cache.dir().display());
+            if entries.is_empty() {
+                println!("no cached databases");
+            }
+            for entry in entries {
+                let todos = match Snapshot::load(&entry.path) {
+                    Ok(snapshot) => format!("{} todos", snapshot.todos.len()),
+                    Err(_) => "unreadable".to_owned(),
+                };
+                println!(
+                    "{}: {todos}, {} bytes, updated {} ago",
+                    entry.database_id,
+                    entry.size,
+                    datetime::format_duration(entry.age())
+                );
+            }
+        }
+        CacheCommand::Clear => println!("removed {} cached databases", cache.clear()?),
+        CacheCommand::Gc { max_age } => {
+            for entry in cache.gc(max_age)? {
+                println!("removed {}", entry.database_id);
+            }
         }
     }
 
     Ok(())
 }
 
-fn push_datetime(datetime: &str, buf: &mut String) {
-    let f = datetime
-        .split_once('T')
-        .inspect(|(ymd, t)| buf.push_str(&format!("{} {}", ymd, &t[..8])));
-    if f.is_none() {
-        buf.push_str(datetime);
+fn process_todos(global: &GlobalArgs, pages: &[Value], names: &PropertyNames) -> Vec<Todo> {
+    let mut todos: Vec<Todo> = Vec::new();
+
+    for (i, page) in pages.iter().enumerate() {
+        match Todo::from_notion_page_with(page, names) {
+            Ok(todo) => todos.push(todo),
+            Err(e) => warn(global, &format!("skipping todo {i}: {e}")),
+        }
     }
+
+    todos
 }
@@ -0,0 +1,134 @@
+use crate::datetime;
+use crate::todo::Todo;
+
+use chrono::{DateTime, TimeDelta, TimeZone};
+
+use std::process::Command;
+use std::time::Duration;
+
+/// A notification about one todo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub summary: &'static str,
+    pub body: String,
+}
+
+/// Reminders for the open todos that are overdue or due within `within`, in
+/// the order of `todos`
+pub fn reminders<Tz>(todos: &[Todo], within: Duration, now: &DateTime<Tz>) -> Vec<Reminder>
+where
+    Tz: TimeZone,
+{
+    let tz = now.timezone();
+    let horizon = TimeDelta::from_std(within)
+        .ok()
+        .and_then(|within| now.clone().checked_add_signed(within));
+
+    todos
+        .iter()
+        .filter(|todo| !todo.done)
+        .filter_map(|todo| {
+            let deadline = todo.deadline(&tz)?;
+            if horizon.as_ref().is_some_and(|horizon| deadline > *horizon) {
+                return None;
+            }
+
+            let mut body = todo.title.clone();
+            if let Some(range) = &todo.due {
+                body.push_str(" (");
+                datetime::push_date_range(range, &mut body);
+                body.push(')');
+            }
+            Some(Reminder {
+                summary: if deadline < *now {
+                    "Overdue"
+                } else {
+                    "Due soon"
+                },
+                body,
+            })
+        })
+        .collect()
+}
+
+/// Shows a desktop notification through the platform's notification service
+pub fn desktop(summary: &str, body: &str) -> crate::Result<()> {
+    let mut command = notification_command(summary, body)?;
+    let program = command.get_program().to_string_lossy().into_owned();
+
+    let status = command
+        .status()
+        .map_err(|e| format!("failed to run {program}: {e}"))?;
+    if !status.success() {
+        return Err(format!("{program} exited with {status}").into());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn notification_command(summary: &str, body: &str) -> crate::Result<Command> {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        quote(body),
+        quote(summary)
+    ));
+    Ok(command)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn notification_command(summary: &str, body: &str) -> crate::Result<Command> {
+    let mut command = Command::new("notify-send");
+    command.args(["--app-name", "notifieru", "--", summary, body]);
+    Ok(command)
+}
+
+#[cfg(not(unix))]
+fn notification_command(_summary: &str, _body: &str) -> crate::Result<Command> {
+    Err("desktop notifications are not supported on this platform yet".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn test_notification_command_ends_options() {
+        let command = notification_command("-v", "--help me").unwrap();
+
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["--app-name", "notifieru", "--", "-v", "--help me"]);
+    }
+
+    #[test]
+    fn test_reminders() {
+        let now: DateTime<Utc> = "2024-05-03T12:00:00Z".parse().unwrap();
+        let todos = [
+            Todo::sample("Late", false, Some("2024-05-01")),
+            Todo::sample("Tonight", false, Some("2024-05-03T20:00:00Z")),
+            Todo::sample("Next week", false, Some("2024-05-10")),
+            Todo::sample("Done", true, Some("2024-05-01")),
+            Todo::sample("Someday", false, None),
+        ];
+
+        let reminders = reminders(&todos, Duration::from_secs(24 * 60 * 60), &now);
+
+        assert_eq!(
+            reminders,
+            [
+                Reminder {
+                    summary: "Overdue",
+                    body: "Late (2024-05-01)".to_owned()
+                },
+                Reminder {
+                    summary: "Due soon",
+                    body: "Tonight (2024-05-03 20:00:00)".to_owned()
+                },
+            ]
+        );
+    }
+}
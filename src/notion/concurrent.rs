@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Notion allows an average of three requests per second per integration
+pub const DEFAULT_CONCURRENCY: usize = 3;
+
+/// Runs `fetch` for every item on at most `limit` threads at once, e.g. a
+/// request per block or page, and returns the outputs in the order of
+/// `items`, e.g. a `crate::Result` each.
+pub fn fetch_bounded<T, O, F>(items: &[T], limit: usize, fetch: F) -> Vec<O>
+where
+    T: Sync,
+    O: Send,
+    F: Fn(&T) -> O + Sync,
+{
+    let workers = limit.clamp(1, items.len().max(1));
+    let next = AtomicUsize::new(0);
+    let outputs: Mutex<Vec<Option<O>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                let output = fetch(item);
+                outputs.lock().unwrap()[i] = Some(output);
+            });
+        }
+    });
+
+    outputs
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|output| output.expect("every item is fetched exactly once"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_fetch_bounded_preserves_order() {
+        let items: Vec<u64> = (0..20).collect();
+
+        let outputs = fetch_bounded(&items, 4, |i| {
+            thread::sleep(Duration::from_millis(20 - i));
+            i * 2
+        });
+
+        assert_eq!(outputs, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_fetch_bounded_respects_limit() {
+        let items: Vec<u32> = (0..12).collect();
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        fetch_bounded(&items, 3, |_| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[test]
+    fn test_fetch_bounded_edge_cases() {
+        assert!(fetch_bounded(&[] as &[u8], 3, |_| ()).is_empty());
+        assert_eq!(fetch_bounded(&[1, 2], 0, |i| i + 1), vec![2, 3]);
+    }
+}
@@ -0,0 +1,98 @@
+use super::client::Reply;
+
+use serde_json::json;
+
+use std::io;
+use std::str::FromStr;
+
+/// A failure the client fakes instead of sending a request, so error paths can
+/// be exercised deterministically without a misbehaving server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Respond like Notion does when the integration is being throttled
+    RateLimit,
+    /// Fail the way minreq does when the connection times out
+    Timeout,
+    /// Return a body that is not valid JSON
+    BadJson,
+}
+
+impl Fault {
+    /// What the client reads in place of Notion's answer on its `attempt`th
+    /// try, which goes through retries like a real one. Throttling is to be
+    /// retried right away, so that `max_attempts` rather than waiting is what
+    /// a run spends on it.
+    pub(super) fn response(self, attempt: u32) -> crate::Result<Reply> {
+        let (status_code, retry_after, body) = match self {
+            Fault::RateLimit => (
+                429,
+                Some("0"),
+                json!({
+                    "object": "error",
+                    "status": 429,
+                    "code": "rate_limited",
+                    "message": format!(
+                        "This request exceeds the number of requests allowed. (injected fault, attempt {attempt})"
+                    )
+                })
+                .to_string(),
+            ),
+            Fault::Timeout => {
+                return Err(minreq::Error::IoError(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "connection timed out (injected fault)",
+                ))
+                .into())
+            }
+            Fault::BadJson => (200, None, "{\"results\": [".to_owned()),
+        };
+        Ok(Reply {
+            status_code,
+            headers: retry_after
+                .map(|secs| ("retry-after".to_owned(), secs.to_owned()))
+                .into_iter()
+                .collect(),
+            body: Box::new(io::Cursor::new(body)),
+        })
+    }
+}
+
+impl FromStr for Fault {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Fault, String> {
+        match s {
+            "rate_limit" => Ok(Fault::RateLimit),
+            "timeout" => Ok(Fault::Timeout),
+            "bad_json" => Ok(Fault::BadJson),
+            _ => Err(format!(
+                "unknown fault '{s}', expected one of: rate_limit, timeout, bad_json"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_from_str() {
+        assert_eq!("rate_limit".parse(), Ok(Fault::RateLimit));
+        assert_eq!("timeout".parse(), Ok(Fault::Timeout));
+        assert_eq!("bad_json".parse(), Ok(Fault::BadJson));
+        assert_eq!(
+            "slow".parse::<Fault>(),
+            Err("unknown fault 'slow', expected one of: rate_limit, timeout, bad_json".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_fault_responses() {
+        let reply = Fault::RateLimit.response(1).unwrap();
+        assert_eq!(reply.status_code, 429);
+        assert_eq!(reply.headers["retry-after"], "0");
+        assert!(Fault::Timeout.response(1).is_err());
+        assert_eq!(Fault::BadJson.response(1).unwrap().status_code, 200);
+    }
+}
@@ -0,0 +1,149 @@
+//! Minimal bindings to the Notion REST API.
+
+mod client;
+mod concurrent;
+mod fault;
+mod page;
+mod proxy;
+mod query;
+mod schema;
+mod version;
+
+pub use client::{
+    Client, List, API_BASE_URL, DEFAULT_MAX_ATTEMPTS, DEFAULT_MAX_PAGES, DEFAULT_MAX_RESPONSE_BYTES,
+};
+pub use concurrent::{fetch_bounded, DEFAULT_CONCURRENCY};
+pub use fault::Fault;
+pub use page::{DateValue, Page, Property, RichText, SelectOption, User};
+pub use proxy::proxy_for;
+pub use query::{
+    CheckboxFilter, DateFilter, Direction, Filter, PeopleFilter, QueryBuilder, SelectFilter,
+    StatusFilter, TextFilter,
+};
+pub use schema::Schema;
+pub use version::{ApiVersion, SUPPORTED_VERSIONS};
+
+/// Splits a database query URL such as
+/// `https://api.notion.com/v1/databases/<id>/query` into the API base URL and
+/// the database id
+pub fn parse_query_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url.trim_end_matches('/').strip_suffix("/query")?;
+    let (base, id) = rest.rsplit_once('/')?;
+    let base = base.strip_suffix("/databases")?;
+
+    if base.is_empty() || id.is_empty() {
+        return None;
+    }
+    Some((base, id))
+}
+
+/// Reads a database as a query URL, a database id with or without dashes, or
+/// a notion.so or notion.site link to it, into the API base URL and the
+/// database id. Only query URLs can point elsewhere than `API_BASE_URL`.
+pub fn parse_database(input: &str) -> Result<(&str, &str), String> {
+    let input = input.trim();
+    if let Some(parsed) = parse_query_url(input) {
+        return Ok(parsed);
+    }
+    if is_database_id(input) {
+        return Ok((API_BASE_URL, input));
+    }
+
+    let link = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+        .unwrap_or(input);
+    let (host, path) = link.split_once('/').unwrap_or((link, ""));
+    if host.ends_with("notion.so") || host.ends_with("notion.site") {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let segment = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default();
+        // Links end in the title, a dash and the undashed id, e.g. Todos-0123…
+        let id = segment
+            .len()
+            .checked_sub(32)
+            .and_then(|start| segment.get(start..))
+            .filter(|_| !is_database_id(segment));
+        match id.unwrap_or(segment) {
+            id if is_database_id(id) => return Ok((API_BASE_URL, id)),
+            _ => return Err(format!("'{input}' doesn't link to a database")),
+        }
+    }
+
+    Err(format!(
+        "'{input}' is not a database ID, notion.so link or database query URL"
+    ))
+}
+
+/// 32 hex digits, bare or dashed like a UUID
+fn is_database_id(s: &str) -> bool {
+    let dashed = s.len() == 36 && [8, 13, 18, 23].iter().all(|&i| s.as_bytes()[i] == b'-');
+    let hex: Vec<_> = s.bytes().filter(|b| *b != b'-').collect();
+    (dashed || s.len() == 32) && hex.len() == 32 && hex.iter().all(u8::is_ascii_hexdigit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_url_success() {
+        let url = "https://api.notion.com/v1/databases/abc123/query";
+        assert_eq!(
+            parse_query_url(url),
+            Some(("https://api.notion.com/v1", "abc123"))
+        );
+    }
+
+    #[test]
+    fn test_parse_query_url_trailing_slash() {
+        let url = "http://localhost:1234/v1/databases/abc123/query/";
+        assert_eq!(
+            parse_query_url(url),
+            Some(("http://localhost:1234/v1", "abc123"))
+        );
+    }
+
+    #[test]
+    fn test_parse_query_url_invalid() {
+        assert_eq!(
+            parse_query_url("https://api.notion.com/v1/databases/abc123"),
+            None
+        );
+        assert_eq!(
+            parse_query_url("https://api.notion.com/v1/pages/abc123/query"),
+            None
+        );
+        assert_eq!(parse_query_url("/databases//query"), None);
+    }
+
+    #[test]
+    fn test_parse_database() {
+        let id = "0123456789abcdef0123456789abcdef";
+        let dashed = "01234567-89ab-cdef-0123-456789abcdef";
+
+        for input in [
+            id.to_owned(),
+            format!("https://www.notion.so/{id}"),
+            format!("https://www.notion.so/acme/Todos-{id}?v=fedcba98&pvs=4"),
+            format!("acme.notion.site/Todos-{id}/"),
+        ] {
+            assert_eq!(parse_database(&input), Ok((API_BASE_URL, id)), "{input}");
+        }
+        assert_eq!(parse_database(dashed), Ok((API_BASE_URL, dashed)));
+        assert_eq!(
+            parse_database("http://localhost:1234/v1/databases/db-1/query"),
+            Ok(("http://localhost:1234/v1", "db-1"))
+        );
+
+        assert!(parse_database("0123456789abcdef").is_err());
+        assert!(parse_database("0123456789abcdef0123456789abcdeg").is_err());
+        assert_eq!(
+            parse_database("https://www.notion.so/acme/Todos"),
+            Err("'https://www.notion.so/acme/Todos' doesn't link to a database".to_owned())
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+
+/// Property types read into a `Property`, tried in this order when a value
+/// doesn't say its type
+const KINDS: [&str; 9] = [
+    "title",
+    "rich_text",
+    "number",
+    "checkbox",
+    "select",
+    "status",
+    "multi_select",
+    "date",
+    "people",
+];
+
+/// A page object, as database queries return them
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Page {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, Property>,
+}
+
+/// The value of a page property, by its type
+#[derive(Debug, Clone, PartialEq)]
+pub enum Property {
+    Title(Vec<RichText>),
+    RichText(Vec<RichText>),
+    Number(Option<f64>),
+    Checkbox(bool),
+    Select(Option<SelectOption>),
+    Status(Option<SelectOption>),
+    MultiSelect(Vec<SelectOption>),
+    Date(Option<DateValue>),
+    People(Vec<User>),
+    /// A type notifieru doesn't read, e.g. files or relations, by its name
+    Other(String),
+    /// A value that doesn't match its type, kept so that one bad property
+    /// only fails the todos that read it. Says what's wrong, to follow the
+    /// property's name, e.g. "has no type".
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RichText {
+    pub plain_text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SelectOption {
+    pub name: String,
+}
+
+/// A person or bot, whose name only comes with a token allowed to read users
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct User {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// ISO 8601 dates or datetimes, as Notion sent them
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DateValue {
+    pub start: String,
+    #[serde(default)]
+    pub end: Option<String>,
+}
+
+impl Property {
+    /// The Notion type name, e.g. `multi_select`
+    pub fn kind(&self) -> &str {
+        match self {
+            Property::Title(_) => "title",
+            Property::RichText(_) => "rich_text",
+            Property::Number(_) => "number",
+            Property::Checkbox(_) => "checkbox",
+            Property::Select(_) => "select",
+            Property::Status(_) => "status",
+            Property::MultiSelect(_) => "multi_select",
+            Property::Date(_) => "date",
+            Property::People(_) => "people",
+            Property::Other(kind) => kind,
+            Property::Invalid(_) => "invalid",
+        }
+    }
+
+    /// The text of a title or rich text property
+    pub fn plain_text(&self) -> Option<String> {
+        match self {
+            Property::Title(parts) | Property::RichText(parts) => {
+                Some(parts.iter().map(|part| part.plain_text.as_str()).collect())
+            }
+            _ => None,
+        }
+    }
+
+    fn from_value(mut property: Value) -> Property {
+        // Hand-written pages, e.g. test fixtures, often leave out the type,
+        // which the key holding the value tells just as well
+        let kind = match property["type"].as_str() {
+            Some(kind) => kind.to_owned(),
+            None => match KINDS.iter().find(|kind| property.get(**kind).is_some()) {
+                Some(kind) => (*kind).to_owned(),
+                None => return Property::Invalid("has no type".to_owned()),
+            },
+        };
+        let value = property[kind.as_str()].take();
+
+        let parsed = match kind.as_str() {
+            "title" => serde_json::from_value(value).map(Property::Title),
+            "rich_text" => serde_json::from_value(value).map(Property::RichText),
+            "number" => serde_json::from_value(value).map(Property::Number),
+            "checkbox" => serde_json::from_value(value).map(Property::Checkbox),
+            "select" => serde_json::from_value(value).map(Property::Select),
+            "status" => serde_json::from_value(value).map(Property::Status),
+            "multi_select" => serde_json::from_value(value).map(Property::MultiSelect),
+            "date" => serde_json::from_value(value).map(Property::Date),
+            "people" => serde_json::from_value(value).map(Property::People),
+            _ => return Property::Other(kind),
+        };
+        parsed.unwrap_or_else(|e| Property::Invalid(format!("is not a valid {kind}: {e}")))
+    }
+}
+
+impl<'de> Deserialize<'de> for Property {
+    fn deserialize<D>(deserializer: D) -> Result<Property, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(Property::from_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_page() {
+        let page: Page = serde_json::from_value(json!({
+            "id": "page-1",
+            "url": "https://www.notion.so/page-1",
+            "properties": {
+                "Name": {"id": "title", "type": "title", "title": [
+                    {"plain_text": "Write "},
+                    {"plain_text": "report"}
+                ]},
+                "Done": {"checkbox": true},
+                "Due": {"type": "date", "date": {"start": "2024-05-01", "end": null}},
+                "Owner": {"type": "people", "people": [
+                    {"object": "user", "id": "user-1", "name": "Ada"}
+                ]},
+                "Files": {"type": "files", "files": []}
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(page.id, "page-1");
+        assert_eq!(
+            page.properties["Name"].plain_text().as_deref(),
+            Some("Write report")
+        );
+        assert_eq!(page.properties["Done"], Property::Checkbox(true));
+        assert_eq!(
+            page.properties["Due"],
+            Property::Date(Some(DateValue {
+                start: "2024-05-01".to_owned(),
+                end: None,
+            }))
+        );
+        assert_eq!(
+            page.properties["Owner"],
+            Property::People(vec![User {
+                id: "user-1".to_owned(),
+                name: Some("Ada".to_owned()),
+            }])
+        );
+        assert_eq!(page.properties["Files"].kind(), "files");
+    }
+
+    #[test]
+    fn test_deserialize_invalid_property() {
+        let property = |value| serde_json::from_value::<Property>(value).unwrap();
+
+        assert_eq!(
+            property(json!({"type": "checkbox", "checkbox": "yes"})),
+            Property::Invalid(
+                "is not a valid checkbox: invalid type: string \"yes\", expected a boolean"
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            property(json!({})),
+            Property::Invalid("has no type".to_owned())
+        );
+    }
+}
@@ -0,0 +1,134 @@
+/// The proxy requests to `url` go through: `configured`, from the config
+/// file, or else `HTTPS_PROXY` or `HTTP_PROXY` by the URL's scheme, falling
+/// back to `ALL_PROXY`. Each variable is also read in lowercase, and `var`
+/// reads them, e.g. from the environment. Hosts listed in `NO_PROXY` go
+/// direct whichever proxy applies.
+pub fn proxy_for<F>(url: &str, configured: Option<&str>, var: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let var = |name: &str| {
+        var(name)
+            .or_else(|| var(&name.to_lowercase()))
+            .filter(|value| !value.trim().is_empty())
+    };
+    let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+    if var("NO_PROXY").is_some_and(|no_proxy| is_exempt(host(rest), &no_proxy)) {
+        return None;
+    }
+
+    let scheme_var = if scheme.eq_ignore_ascii_case("http") {
+        "HTTP_PROXY"
+    } else {
+        "HTTPS_PROXY"
+    };
+    configured
+        .map(str::to_owned)
+        .or_else(|| var(scheme_var))
+        .or_else(|| var("ALL_PROXY"))
+}
+
+/// The host of a URL without its scheme, e.g. `api.notion.com` of
+/// `user@api.notion.com:443/v1`
+fn host(authority_and_path: &str) -> &str {
+    let authority = authority_and_path
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host_and_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    match host_and_port.strip_prefix('[') {
+        // IPv6, e.g. [::1]:8080
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host_and_port.split(':').next().unwrap_or_default(),
+    }
+}
+
+/// Whether `NO_PROXY`, a comma-separated list of hosts and domains, covers
+/// `host`. A domain covers its subdomains, with or without a leading dot,
+/// and `*` covers every host. Ports and address ranges aren't told apart.
+fn is_exempt(host: &str, no_proxy: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    no_proxy.split(',').any(|entry| {
+        let entry = entry.trim().to_ascii_lowercase();
+        if entry == "*" {
+            return true;
+        }
+        let entry = entry.trim_start_matches("*.").trim_start_matches('.');
+        let entry = match entry.rsplit_once(':') {
+            Some((name, port)) if !name.contains(':') && port.parse::<u16>().is_ok() => name,
+            _ => entry.trim_start_matches('[').trim_end_matches(']'),
+        };
+        !entry.is_empty()
+            && (host == entry
+                || host
+                    .strip_suffix(entry)
+                    .is_some_and(|sub| sub.ends_with('.')))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    #[test]
+    fn test_proxy_for() {
+        let vars = [
+            ("HTTPS_PROXY", "http://secure:3128"),
+            ("http_proxy", "http://plain:3128"),
+        ];
+        let url = "https://api.notion.com/v1";
+
+        assert_eq!(
+            proxy_for(url, None, env(&vars)).as_deref(),
+            Some("http://secure:3128")
+        );
+        assert_eq!(
+            proxy_for("http://127.0.0.1:8080/v1", None, env(&vars)).as_deref(),
+            Some("http://plain:3128")
+        );
+        assert_eq!(
+            proxy_for(url, Some("http://configured:8080"), env(&vars)).as_deref(),
+            Some("http://configured:8080")
+        );
+        assert_eq!(
+            proxy_for(url, None, env(&[("all_proxy", "http://any:1080")])).as_deref(),
+            Some("http://any:1080")
+        );
+        assert_eq!(proxy_for(url, None, env(&[("HTTPS_PROXY", "")])), None);
+        assert_eq!(proxy_for(url, None, env(&[])), None);
+    }
+
+    #[test]
+    fn test_proxy_for_no_proxy() {
+        let url = "https://api.notion.com/v1";
+        let proxied = |no_proxy| {
+            let vars = [("HTTPS_PROXY", "http://proxy:3128"), ("no_proxy", no_proxy)];
+            proxy_for(url, Some("http://configured:8080"), env(&vars)).is_some()
+        };
+
+        assert!(!proxied("localhost,notion.com"));
+        assert!(!proxied(".notion.com"));
+        assert!(!proxied("api.notion.com:443"));
+        assert!(!proxied("*"));
+        assert!(proxied("localhost,tion.com"));
+        assert!(proxied(""));
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(host("api.notion.com/v1"), "api.notion.com");
+        assert_eq!(host("user:secret@proxy:3128"), "proxy");
+        assert_eq!(host("[::1]:8080/v1"), "::1");
+        assert!(is_exempt("::1", "[::1]"));
+    }
+}
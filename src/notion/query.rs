@@ -0,0 +1,333 @@
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+/// Builds the body of a database query request
+#[derive(Debug, Clone, Default)]
+pub struct QueryBuilder {
+    filter: Option<Filter>,
+    sorts: Vec<Value>,
+    page_size: Option<u32>,
+    start_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl Direction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Direction::Ascending => "ascending",
+            Direction::Descending => "descending",
+        }
+    }
+}
+
+impl QueryBuilder {
+    pub fn new() -> QueryBuilder {
+        QueryBuilder::default()
+    }
+
+    pub fn filter(mut self, filter: Filter) -> QueryBuilder {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Sorts are applied in the order they are added
+    pub fn sort(mut self, property: &str, direction: Direction) -> QueryBuilder {
+        self.sorts
+            .push(json!({"property": property, "direction": direction.as_str()}));
+        self
+    }
+
+    /// Notion caps the page size at 100, larger values are clamped
+    pub fn page_size(mut self, page_size: u32) -> QueryBuilder {
+        self.page_size = Some(page_size.clamp(1, 100));
+        self
+    }
+
+    pub fn start_cursor<T>(mut self, cursor: T) -> QueryBuilder
+    where
+        T: Into<String>,
+    {
+        self.start_cursor = Some(cursor.into());
+        self
+    }
+
+    pub fn build(&self) -> Value {
+        let mut body = Map::new();
+
+        if let Some(Filter(filter)) = &self.filter {
+            body.insert("filter".to_owned(), filter.clone());
+        }
+        if !self.sorts.is_empty() {
+            body.insert("sorts".to_owned(), Value::from(self.sorts.clone()));
+        }
+        if let Some(page_size) = self.page_size {
+            body.insert("page_size".to_owned(), Value::from(page_size));
+        }
+        if let Some(cursor) = &self.start_cursor {
+            body.insert("start_cursor".to_owned(), Value::from(cursor.as_str()));
+        }
+
+        Value::Object(body)
+    }
+}
+
+/// A property filter condition. Values are created through the typed
+/// constructors so only conditions valid for a property type can be built.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter(Value);
+
+impl Filter {
+    pub fn checkbox(property: &str) -> CheckboxFilter {
+        CheckboxFilter(property.to_owned())
+    }
+
+    pub fn date(property: &str) -> DateFilter {
+        DateFilter(property.to_owned())
+    }
+
+    pub fn select(property: &str) -> SelectFilter {
+        SelectFilter(property.to_owned())
+    }
+
+    pub fn status(property: &str) -> StatusFilter {
+        StatusFilter(property.to_owned())
+    }
+
+    pub fn people(property: &str) -> PeopleFilter {
+        PeopleFilter(property.to_owned())
+    }
+
+    pub fn rich_text(property: &str) -> TextFilter {
+        TextFilter(property.to_owned(), "rich_text")
+    }
+
+    pub fn title(property: &str) -> TextFilter {
+        TextFilter(property.to_owned(), "title")
+    }
+
+    /// Matches when every one of `filters` does. Notion accepts compound
+    /// filters nested two levels deep at most.
+    pub fn and(filters: Vec<Filter>) -> Filter {
+        Filter::compound("and", filters)
+    }
+
+    /// Matches when any one of `filters` does
+    pub fn or(filters: Vec<Filter>) -> Filter {
+        Filter::compound("or", filters)
+    }
+
+    pub fn to_json(&self) -> &Value {
+        &self.0
+    }
+
+    fn condition(property: &str, kind: &str, condition: &str, value: Value) -> Filter {
+        Filter(json!({"property": property, kind: {condition: value}}))
+    }
+
+    fn compound(operator: &str, filters: Vec<Filter>) -> Filter {
+        let filters: Vec<_> = filters.into_iter().map(|Filter(filter)| filter).collect();
+        Filter(json!({operator: filters}))
+    }
+}
+
+pub struct CheckboxFilter(String);
+
+impl CheckboxFilter {
+    pub fn equals(self, value: bool) -> Filter {
+        Filter::condition(&self.0, "checkbox", "equals", Value::from(value))
+    }
+
+    pub fn does_not_equal(self, value: bool) -> Filter {
+        Filter::condition(&self.0, "checkbox", "does_not_equal", Value::from(value))
+    }
+}
+
+/// Dates are ISO 8601 strings, either a date (`2024-05-01`) or a datetime
+pub struct DateFilter(String);
+
+impl DateFilter {
+    pub fn equals(self, date: &str) -> Filter {
+        Filter::condition(&self.0, "date", "equals", Value::from(date))
+    }
+
+    pub fn before(self, date: &str) -> Filter {
+        Filter::condition(&self.0, "date", "before", Value::from(date))
+    }
+
+    pub fn after(self, date: &str) -> Filter {
+        Filter::condition(&self.0, "date", "after", Value::from(date))
+    }
+
+    pub fn on_or_before(self, date: &str) -> Filter {
+        Filter::condition(&self.0, "date", "on_or_before", Value::from(date))
+    }
+
+    pub fn on_or_after(self, date: &str) -> Filter {
+        Filter::condition(&self.0, "date", "on_or_after", Value::from(date))
+    }
+
+    pub fn is_empty(self) -> Filter {
+        Filter::condition(&self.0, "date", "is_empty", Value::from(true))
+    }
+
+    pub fn is_not_empty(self) -> Filter {
+        Filter::condition(&self.0, "date", "is_not_empty", Value::from(true))
+    }
+}
+
+pub struct SelectFilter(String);
+
+impl SelectFilter {
+    pub fn equals(self, option: &str) -> Filter {
+        Filter::condition(&self.0, "select", "equals", Value::from(option))
+    }
+
+    pub fn does_not_equal(self, option: &str) -> Filter {
+        Filter::condition(&self.0, "select", "does_not_equal", Value::from(option))
+    }
+
+    pub fn is_empty(self) -> Filter {
+        Filter::condition(&self.0, "select", "is_empty", Value::from(true))
+    }
+
+    pub fn is_not_empty(self) -> Filter {
+        Filter::condition(&self.0, "select", "is_not_empty", Value::from(true))
+    }
+}
+
+pub struct StatusFilter(String);
+
+impl StatusFilter {
+    pub fn equals(self, option: &str) -> Filter {
+        Filter::condition(&self.0, "status", "equals", Value::from(option))
+    }
+
+    pub fn does_not_equal(self, option: &str) -> Filter {
+        Filter::condition(&self.0, "status", "does_not_equal", Value::from(option))
+    }
+}
+
+/// People are matched by Notion user id
+pub struct PeopleFilter(String);
+
+impl PeopleFilter {
+    pub fn contains(self, user_id: &str) -> Filter {
+        Filter::condition(&self.0, "people", "contains", Value::from(user_id))
+    }
+
+    pub fn does_not_contain(self, user_id: &str) -> Filter {
+        Filter::condition(&self.0, "people", "does_not_contain", Value::from(user_id))
+    }
+
+    pub fn is_empty(self) -> Filter {
+        Filter::condition(&self.0, "people", "is_empty", Value::from(true))
+    }
+
+    pub fn is_not_empty(self) -> Filter {
+        Filter::condition(&self.0, "people", "is_not_empty", Value::from(true))
+    }
+}
+
+/// Text is matched case-insensitively by Notion
+pub struct TextFilter(String, &'static str);
+
+impl TextFilter {
+    pub fn equals(self, text: &str) -> Filter {
+        Filter::condition(&self.0, self.1, "equals", Value::from(text))
+    }
+
+    pub fn contains(self, text: &str) -> Filter {
+        Filter::condition(&self.0, self.1, "contains", Value::from(text))
+    }
+
+    pub fn does_not_contain(self, text: &str) -> Filter {
+        Filter::condition(&self.0, self.1, "does_not_contain", Value::from(text))
+    }
+
+    pub fn is_empty(self) -> Filter {
+        Filter::condition(&self.0, self.1, "is_empty", Value::from(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_empty_query() {
+        assert_eq!(QueryBuilder::new().build(), json!({}));
+    }
+
+    #[test]
+    fn test_build_full_query() {
+        let query = QueryBuilder::new()
+            .filter(Filter::checkbox("Done").equals(false))
+            .sort("Due", Direction::Ascending)
+            .sort("Name", Direction::Descending)
+            .page_size(500)
+            .start_cursor("cursor-1")
+            .build();
+
+        assert_eq!(
+            query,
+            json!({
+                "filter": {"property": "Done", "checkbox": {"equals": false}},
+                "sorts": [
+                    {"property": "Due", "direction": "ascending"},
+                    {"property": "Name", "direction": "descending"}
+                ],
+                "page_size": 100,
+                "start_cursor": "cursor-1"
+            })
+        );
+    }
+
+    #[test]
+    fn test_filter_constructors() {
+        assert_eq!(
+            Filter::date("Due").on_or_before("2024-05-01").to_json(),
+            &json!({"property": "Due", "date": {"on_or_before": "2024-05-01"}})
+        );
+        assert_eq!(
+            Filter::select("Priority").is_empty().to_json(),
+            &json!({"property": "Priority", "select": {"is_empty": true}})
+        );
+        assert_eq!(
+            Filter::people("Assignee").contains("user-1").to_json(),
+            &json!({"property": "Assignee", "people": {"contains": "user-1"}})
+        );
+        assert_eq!(
+            Filter::title("Name").contains("rent").to_json(),
+            &json!({"property": "Name", "title": {"contains": "rent"}})
+        );
+    }
+
+    #[test]
+    fn test_compound_filters() {
+        let filter = Filter::and(vec![
+            Filter::checkbox("Done").equals(false),
+            Filter::or(vec![
+                Filter::date("Due").before("2024-06-01"),
+                Filter::rich_text("Notes").is_empty(),
+            ]),
+        ]);
+
+        assert_eq!(
+            filter.to_json(),
+            &json!({"and": [
+                {"property": "Done", "checkbox": {"equals": false}},
+                {"or": [
+                    {"property": "Due", "date": {"before": "2024-06-01"}},
+                    {"property": "Notes", "rich_text": {"is_empty": true}}
+                ]}
+            ]})
+        );
+    }
+}
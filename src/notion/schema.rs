@@ -0,0 +1,158 @@
+use super::Client;
+use crate::todo::PropertyNames;
+
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+
+/// Words a property name is likely to contain, per field, in order of
+/// preference
+const DUE_HINTS: [&str; 3] = ["due", "deadline", "date"];
+const DONE_HINTS: [&str; 5] = ["done", "complete", "finished", "check", "status"];
+
+/// The property names and types of a database, and the options of its
+/// selects and statuses
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    types: BTreeMap<String, String>,
+    options: BTreeMap<String, Vec<String>>,
+}
+
+impl Schema {
+    pub fn fetch(client: &Client, database_id: &str) -> crate::Result<Schema> {
+        Ok(Schema::from_properties(
+            &client.database_properties(database_id)?,
+        ))
+    }
+
+    /// Reads a `properties` object, of a database or of one of its pages
+    pub fn from_properties(properties: &Value) -> Schema {
+        let types = properties
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, property)| {
+                let kind = property["type"].as_str()?;
+                Some((name.clone(), kind.to_owned()))
+            })
+            .collect();
+        let options = properties
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, property)| {
+                let kind = property["type"].as_str()?;
+                let options = property[kind]["options"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|option| option["name"].as_str())
+                    .map(str::to_owned)
+                    .collect();
+                Some((name.clone(), options))
+            })
+            .collect();
+        Schema { types, options }
+    }
+
+    /// The type of the property `name`, e.g. `checkbox`
+    pub fn kind(&self, name: &str) -> Option<&str> {
+        self.types.get(name).map(String::as_str)
+    }
+
+    /// The names of the properties of type `kind`, in alphabetical order
+    pub fn of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a str> {
+        self.types
+            .iter()
+            .filter(move |(_, actual)| *actual == kind)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The options of the select, multi-select or status property `name`, or
+    /// none when the schema doesn't list them
+    pub fn options(&self, name: &str) -> &[String] {
+        self.options.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// `names` with the title, due date and done checkbox (or status, with
+    /// `done_when`) pointed at properties of the right type wherever the named
+    /// ones don't have it. Names hinting at the field win, then the first
+    /// property of the type.
+    pub fn detect(&self, names: &PropertyNames) -> PropertyNames {
+        let mut detected = names.clone();
+        for (name, kind, hints) in [
+            (&mut detected.title, "title", &[][..]),
+            (&mut detected.due, "date", &DUE_HINTS[..]),
+            (&mut detected.done, names.done_when.kind(), &DONE_HINTS[..]),
+        ] {
+            if self.kind(name) == Some(kind) {
+                continue;
+            }
+            let candidates: Vec<_> = self.of_kind(kind).collect();
+            let hinted = hints.iter().find_map(|hint| {
+                candidates
+                    .iter()
+                    .find(|candidate| candidate.to_lowercase().contains(hint))
+            });
+            if let Some(candidate) = hinted.or(candidates.first()) {
+                *name = (*candidate).to_owned();
+            }
+        }
+        detected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect() {
+        let schema = Schema::from_properties(&json!({
+            "Task": {"type": "title"},
+            "Created": {"type": "date"},
+            "Deadline": {"type": "date"},
+            "Archived": {"type": "checkbox"},
+            "Tags": {"type": "multi_select"}
+        }));
+
+        let names = schema.detect(&PropertyNames::default());
+
+        assert_eq!(names.title, "Task");
+        assert_eq!(names.due, "Deadline");
+        // Nothing hints at done, so the only checkbox it is
+        assert_eq!(names.done, "Archived");
+        assert_eq!(names.tags, "Tags");
+    }
+
+    #[test]
+    fn test_detect_keeps_fitting_names() {
+        let schema = Schema::from_properties(&json!({
+            "Name": {"type": "title"},
+            "Due": {"type": "date"},
+            "Reminder": {"type": "date"},
+            "Done": {"type": "checkbox"}
+        }));
+
+        assert_eq!(
+            schema.detect(&PropertyNames::default()),
+            PropertyNames::default()
+        );
+        assert_eq!(schema.kind("Reminder"), Some("date"));
+    }
+
+    #[test]
+    fn test_options() {
+        let schema = Schema::from_properties(&json!({
+            "Status": {"type": "status", "status": {"options": [
+                {"name": "Not started"},
+                {"name": "Done"}
+            ]}},
+            "Due": {"type": "date", "date": {}}
+        }));
+
+        assert_eq!(schema.options("Status"), ["Not started", "Done"]);
+        assert!(schema.options("Due").is_empty());
+        assert!(schema.options("Missing").is_empty());
+    }
+}
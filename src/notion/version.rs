@@ -0,0 +1,88 @@
+use serde::Deserialize;
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The `Notion-Version` values the client knows how to talk to.
+///
+/// Newer versions can change endpoint shapes; the client adapts requests and
+/// responses so callers see the same results whichever version is selected.
+/// Variants are declared oldest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ApiVersion {
+    #[default]
+    V2022_06_28,
+    /// Databases became containers of data sources, which are queried instead
+    V2025_09_03,
+}
+
+pub const SUPPORTED_VERSIONS: [ApiVersion; 2] = [ApiVersion::V2022_06_28, ApiVersion::V2025_09_03];
+
+impl ApiVersion {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiVersion::V2022_06_28 => "2022-06-28",
+            ApiVersion::V2025_09_03 => "2025-09-03",
+        }
+    }
+
+    pub(crate) fn has_data_sources(self) -> bool {
+        self >= ApiVersion::V2025_09_03
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ApiVersion, String> {
+        SUPPORTED_VERSIONS
+            .into_iter()
+            .find(|version| version.as_str() == s)
+            .ok_or_else(|| {
+                let supported: Vec<_> = SUPPORTED_VERSIONS.iter().map(|v| v.as_str()).collect();
+                format!(
+                    "unsupported Notion-Version '{s}', expected one of: {}",
+                    supported.join(", ")
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for ApiVersion {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<ApiVersion, String> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_version_from_str() {
+        assert_eq!("2022-06-28".parse(), Ok(ApiVersion::V2022_06_28));
+        assert_eq!("2025-09-03".parse(), Ok(ApiVersion::V2025_09_03));
+        assert_eq!(
+            "2021-05-13".parse::<ApiVersion>(),
+            Err(
+                "unsupported Notion-Version '2021-05-13', expected one of: 2022-06-28, 2025-09-03"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn test_api_version_order() {
+        assert!(!ApiVersion::V2022_06_28.has_data_sources());
+        assert!(ApiVersion::V2025_09_03.has_data_sources());
+    }
+}
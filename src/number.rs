@@ -0,0 +1,218 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Currency formats Notion offers, with the symbol each is shown with
+const CURRENCIES: [(&str, &str); 24] = [
+    ("dollar", "$"),
+    ("canadian_dollar", "CA$"),
+    ("australian_dollar", "A$"),
+    ("singapore_dollar", "S$"),
+    ("hong_kong_dollar", "HK$"),
+    ("new_zealand_dollar", "NZ$"),
+    ("new_taiwan_dollar", "NT$"),
+    ("euro", "€"),
+    ("pound", "£"),
+    ("yen", "¥"),
+    ("yuan", "CN¥"),
+    ("won", "₩"),
+    ("rupee", "₹"),
+    ("ruble", "₽"),
+    ("real", "R$"),
+    ("lira", "₺"),
+    ("rupiah", "Rp"),
+    ("franc", "CHF "),
+    ("krona", "kr "),
+    ("norwegian_krone", "kr "),
+    ("danish_krone", "kr "),
+    ("zloty", "zł "),
+    ("baht", "฿"),
+    ("shekel", "₪"),
+];
+
+/// How Notion shows a number property, from its `number.format` in the
+/// database schema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberFormat {
+    #[default]
+    Plain,
+    /// Grouped thousands, e.g. 1,234.5
+    Grouped,
+    /// Stored as a fraction, so 0.25 shows as 25%
+    Percent,
+    /// Grouped with two decimals after the symbol, e.g. $1,234.50
+    Currency(&'static str),
+}
+
+impl NumberFormat {
+    /// Formats Notion added later, or currencies without a known symbol, read
+    /// as plain grouped numbers rather than failing the run
+    pub fn from_notion(format: &str) -> NumberFormat {
+        match format {
+            "number" => NumberFormat::Plain,
+            "number_with_commas" => NumberFormat::Grouped,
+            "percent" => NumberFormat::Percent,
+            format => CURRENCIES
+                .iter()
+                .find(|(name, _)| *name == format)
+                .map_or(NumberFormat::Grouped, |(_, symbol)| {
+                    NumberFormat::Currency(symbol)
+                }),
+        }
+    }
+
+    pub fn format(self, number: f64, locale: Locale) -> String {
+        let sign = if number < 0.0 { "-" } else { "" };
+        let number = number.abs();
+        match self {
+            NumberFormat::Plain => format!("{sign}{}", locale.digits(&number.to_string(), false)),
+            NumberFormat::Grouped => format!("{sign}{}", locale.digits(&number.to_string(), true)),
+            NumberFormat::Percent => {
+                // Rounds away float noise such as 0.29 * 100 = 28.999999999999996
+                let percent = (number * 100.0 * 1e9).round() / 1e9;
+                format!("{sign}{}%", locale.digits(&percent.to_string(), true))
+            }
+            NumberFormat::Currency(symbol) => {
+                format!(
+                    "{sign}{symbol}{}",
+                    locale.digits(&format!("{number:.2}"), true)
+                )
+            }
+        }
+    }
+}
+
+/// The number format of every number property in a database's property
+/// schema, as `Client::database_properties` returns it
+pub fn formats(properties: &Value) -> BTreeMap<String, NumberFormat> {
+    let Some(properties) = properties.as_object() else {
+        return BTreeMap::new();
+    };
+    properties
+        .iter()
+        .filter(|(_, property)| property["type"] == "number")
+        .map(|(name, property)| {
+            let format = property["number"]["format"].as_str().unwrap_or("number");
+            (name.clone(), NumberFormat::from_notion(format))
+        })
+        .collect()
+}
+
+/// Separators numbers are written with, picked by language, e.g. `de` or
+/// `fr-CA`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Locale {
+    thousands: char,
+    decimal: char,
+}
+
+impl Default for Locale {
+    fn default() -> Locale {
+        Locale {
+            thousands: ',',
+            decimal: '.',
+        }
+    }
+}
+
+impl Locale {
+    /// Rewrites plain `1234.5` style digits with this locale's separators
+    fn digits(self, digits: &str, grouped: bool) -> String {
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let mut buf = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if grouped && i > 0 && (integer.len() - i) % 3 == 0 {
+                buf.push(self.thousands);
+            }
+            buf.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            buf.push(self.decimal);
+            buf.push_str(fraction);
+        }
+        buf
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Locale, String> {
+        let language = s.split(['-', '_']).next().unwrap_or_default();
+        let (thousands, decimal) = match language.to_ascii_lowercase().as_str() {
+            "en" | "ja" | "ko" | "zh" | "th" | "he" => (',', '.'),
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" => ('.', ','),
+            "fr" | "sv" | "nb" | "fi" | "pl" | "cs" | "ru" | "uk" => ('\u{a0}', ','),
+            _ => return Err(format!("unknown locale '{s}', expected e.g. en, de or fr")),
+        };
+        Ok(Locale { thousands, decimal })
+    }
+}
+
+impl TryFrom<String> for Locale {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Locale, String> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format() {
+        let en = Locale::default();
+
+        assert_eq!(NumberFormat::Plain.format(1234.5, en), "1234.5");
+        assert_eq!(NumberFormat::Grouped.format(1234567.0, en), "1,234,567");
+        assert_eq!(NumberFormat::Percent.format(0.29, en), "29%");
+        assert_eq!(
+            NumberFormat::Currency("$").format(-1234.5, en),
+            "-$1,234.50"
+        );
+        assert_eq!(
+            NumberFormat::from_notion("euro"),
+            NumberFormat::Currency("€")
+        );
+        assert_eq!(
+            NumberFormat::from_notion("new_currency"),
+            NumberFormat::Grouped
+        );
+    }
+
+    #[test]
+    fn test_locale() {
+        let de: Locale = "de-DE".parse().unwrap();
+
+        assert_eq!(NumberFormat::Currency("€").format(1234.5, de), "€1.234,50");
+        assert_eq!(NumberFormat::Plain.format(0.5, de), "0,5");
+        assert!("xx".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_formats() {
+        let properties = json!({
+            "Budget": {"type": "number", "number": {"format": "dollar"}},
+            "Estimate": {"type": "number", "number": {"format": "number"}},
+            "Name": {"type": "title", "title": {}}
+        });
+
+        assert_eq!(
+            formats(&properties),
+            BTreeMap::from([
+                ("Budget".to_owned(), NumberFormat::Currency("$")),
+                ("Estimate".to_owned(), NumberFormat::Plain),
+            ])
+        );
+    }
+}
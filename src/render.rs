@@ -0,0 +1,641 @@
+use crate::datetime::{self, push_date_range};
+use crate::diff::Diff;
+use crate::number::{Locale, NumberFormat};
+use crate::review::Review;
+use crate::snapshot::Snapshot;
+use crate::todo::{PropertyValue, Todo};
+
+use chrono::{DateTime, TimeZone};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Table,
+    Json,
+    /// GitHub Actions workflow commands
+    Gha,
+    /// Lines for an editor's quickfix or problems list, see `quickfix`
+    Quickfix,
+    /// An Org mode file for org-agenda
+    Org,
+    /// A fixed-size block of plain text for desktop widgets, see `widget`
+    Widget,
+    /// Alfred's Script Filter JSON, which Raycast reads too, see `alfred`
+    Alfred,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "gha" => Ok(Format::Gha),
+            "quickfix" => Ok(Format::Quickfix),
+            "org" => Ok(Format::Org),
+            "widget" => Ok(Format::Widget),
+            "alfred" => Ok(Format::Alfred),
+            _ => Err(format!(
+                "unknown format '{s}', expected table, json, gha, quickfix, org, widget or alfred"
+            )),
+        }
+    }
+}
+
+/// Renders a listing in any format, so one fetch can feed several outputs.
+/// `query` only applies to JSON and `table_options` to tables. The result
+/// always ends with a newline.
+pub fn listing<Tz>(
+    format: Format,
+    snapshot: &Snapshot,
+    query: Option<&str>,
+    table_options: &TableOptions,
+    now: &DateTime<Tz>,
+) -> crate::Result<String>
+where
+    Tz: TimeZone,
+{
+    Ok(match (format, query) {
+        (Format::Table, _) => table_with(&snapshot.todos, table_options),
+        (Format::Json, None) => json(snapshot)? + "\n",
+        (Format::Json, Some(query)) => json_query(snapshot, query)? + "\n",
+        (Format::Gha, _) => gha_annotations(&snapshot.todos, now),
+        (Format::Quickfix, _) => quickfix(&snapshot.todos, now),
+        (Format::Org, _) => org(&snapshot.todos),
+        (Format::Widget, _) => widget(&snapshot.todos, table_options, now),
+        (Format::Alfred, _) => alfred(&snapshot.todos, now)? + "\n",
+    })
+}
+
+/// Title column width the table pads to by default
+const TITLE_WIDTH: usize = 35;
+/// Narrowest title column `fit_title_width` will pick
+const MIN_TITLE_WIDTH: usize = 10;
+/// What the table shows after each todo's checkbox and index by default
+pub const DEFAULT_COLUMNS: [&str; 2] = ["title", "due"];
+/// Between the columns of a table row
+const COLUMN_SEPARATOR: &str = " | ";
+
+/// How `table` lays out its rows
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableOptions {
+    /// Cuts longer titles to this many characters, or wraps them with `wrap`
+    pub title_width: Option<usize>,
+    /// Wraps long titles across lines instead of cutting them
+    pub wrap: bool,
+    /// Shows only the first rows, followed by a count of the hidden ones
+    pub max_rows: Option<usize>,
+    /// Built-in fields or other properties to show, title and due date when
+    /// empty
+    pub columns: Vec<String>,
+    /// How number columns are shown, plain when missing
+    pub number_formats: BTreeMap<String, NumberFormat>,
+    pub locale: Locale,
+}
+
+impl TableOptions {
+    fn columns(&self) -> Vec<&str> {
+        if self.columns.is_empty() {
+            return DEFAULT_COLUMNS.to_vec();
+        }
+        self.columns.iter().map(String::as_str).collect()
+    }
+}
+
+/// The default human-readable listing, one todo per line
+pub fn table(todos: &[Todo]) -> String {
+    table_with(todos, &TableOptions::default())
+}
+
+pub fn table_with(todos: &[Todo], options: &TableOptions) -> String {
+    let shown = options.max_rows.unwrap_or(todos.len()).min(todos.len());
+    let columns = options.columns();
+    let widths = column_widths(&todos[..shown], &columns, options);
+
+    let mut buf = String::new();
+    for (i, todo) in todos[..shown].iter().enumerate() {
+        push_table_row(i, todo, &columns, &widths, options, &mut buf);
+        buf.push('\n');
+    }
+    if shown < todos.len() {
+        buf.push_str(&format!("…and {} more\n", todos.len() - shown));
+    }
+    buf
+}
+
+/// The title width that lets every row fit into a terminal `terminal_width`
+/// columns wide
+pub fn fit_title_width(todos: &[Todo], options: &TableOptions, terminal_width: usize) -> usize {
+    let index_width = todos.len().saturating_sub(1).to_string().len();
+    let columns = options.columns();
+    let others: usize = column_widths(todos, &columns, options)
+        .iter()
+        .zip(&columns)
+        .filter(|(_, column)| **column != "title")
+        .map(|(width, _)| width)
+        .sum();
+    // "[x] ", the index, ": " and the separators
+    let separators = COLUMN_SEPARATOR.len() * columns.len().saturating_sub(1);
+    let fixed = 4 + index_width + 2 + separators + others;
+
+    terminal_width.saturating_sub(fixed).max(MIN_TITLE_WIDTH)
+}
+
+/// The title's configured width, and every other column's widest cell
+fn column_widths(todos: &[Todo], columns: &[&str], options: &TableOptions) -> Vec<usize> {
+    columns
+        .iter()
+        .map(|column| match *column {
+            "title" => options.title_width.unwrap_or(TITLE_WIDTH),
+            column => todos
+                .iter()
+                .map(|todo| cell(todo, column, options).chars().count())
+                .max()
+                .unwrap_or(0),
+        })
+        .collect()
+}
+
+/// A column's text for `todo`, empty when it has no value
+fn cell(todo: &Todo, column: &str, options: &TableOptions) -> String {
+    match column {
+        "due" => {
+            let mut buf = String::new();
+            if let Some(due) = &todo.due {
+                push_date_range(due, &mut buf);
+            }
+            buf
+        }
+        "tags" => todo.tags.join(", "),
+        column => match todo.property(column) {
+            Some(PropertyValue::Number(number)) => {
+                let format = options.number_formats.get(column).copied();
+                format.unwrap_or_default().format(number, options.locale)
+            }
+            Some(value) => value.to_string(),
+            None => String::new(),
+        },
+    }
+}
+
+fn push_table_row(
+    i: usize,
+    todo: &Todo,
+    columns: &[&str],
+    widths: &[usize],
+    options: &TableOptions,
+    buf: &mut String,
+) {
+    let prefix = format!("[{}] {}: ", if todo.done { "x" } else { " " }, i);
+    let lines = match options.title_width {
+        Some(width) if options.wrap => wrap(&todo.title, width),
+        Some(width) => vec![truncate(&todo.title, width)],
+        None => vec![todo.title.clone()],
+    };
+
+    // The last column isn't padded, except the title for the wrapped lines'
+    // sake
+    let last = columns.len().saturating_sub(1);
+    let cells: Vec<_> = columns
+        .iter()
+        .zip(widths)
+        .enumerate()
+        .map(|(c, (column, width))| match *column {
+            "title" => format!("{:width$}", lines[0]),
+            column if c < last => format!("{:width$}", cell(todo, column, options)),
+            column => cell(todo, column, options),
+        })
+        .collect();
+    buf.push_str(&prefix);
+    buf.push_str(&cells.join(COLUMN_SEPARATOR));
+
+    // Continuation lines line up under the title column
+    let title_at = columns.iter().position(|column| *column == "title");
+    let indent = title_at.map_or(0, |at| {
+        prefix.chars().count()
+            + widths[..at]
+                .iter()
+                .map(|width| width + COLUMN_SEPARATOR.len())
+                .sum::<usize>()
+    });
+    for line in &lines[1..] {
+        buf.push('\n');
+        buf.push_str(&" ".repeat(indent));
+        buf.push_str(line);
+    }
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_owned();
+    }
+    let mut cut: String = s.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Breaks at spaces where possible, and mid-word for words wider than `width`
+fn wrap(s: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![String::new()];
+    for word in s.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        loop {
+            let line = lines.last_mut().unwrap();
+            let used = line.chars().count();
+            let space = usize::from(used > 0);
+            if used + space + word.len() <= width {
+                if space == 1 {
+                    line.push(' ');
+                }
+                line.extend(word);
+                break;
+            }
+            if used > 0 {
+                lines.push(String::new());
+                continue;
+            }
+            line.extend(&word[..width]);
+            word.drain(..width);
+            lines.push(String::new());
+        }
+    }
+    lines
+}
+
+/// The snapshot format, for scripts and for re-reading as a fixture
+pub fn json(snapshot: &Snapshot) -> crate::Result<String> {
+    Ok(serde_json::to_string_pretty(snapshot)?)
+}
+
+/// Applies a JMESPath expression to the JSON output, e.g.
+/// `todos[?!done].title`, and renders the result as JSON
+pub fn json_query(snapshot: &Snapshot, expression: &str) -> crate::Result<String> {
+    let expression =
+        jmespath::compile(expression).map_err(|e| format!("invalid --query expression: {e}"))?;
+    let result = expression.search(snapshot).map_err(|e| e.to_string())?;
+    Ok(serde_json::to_string_pretty(&result)?)
+}
+
+/// One line per change, grouped by kind
+pub fn diff(diff: &Diff) -> String {
+    if diff.is_empty() {
+        return "no changes\n".to_owned();
+    }
+
+    let mut buf = String::new();
+    for (kind, todos) in [
+        ("added", &diff.added),
+        ("removed", &diff.removed),
+        ("completed", &diff.completed),
+        ("reopened", &diff.reopened),
+    ] {
+        for todo in todos {
+            push_diff_line(kind, todo, &mut buf);
+            buf.push('\n');
+        }
+    }
+    for (before, after) in &diff.rescheduled {
+        push_diff_line("rescheduled", before, &mut buf);
+        buf.push_str(" -> ");
+        match &after.due {
+            Some(due) => push_date_range(due, &mut buf),
+            None => buf.push_str("no date"),
+        }
+        buf.push('\n');
+    }
+    buf
+}
+
+fn push_diff_line(kind: &str, todo: &Todo, buf: &mut String) {
+    buf.push_str(&format!("{:12} {:35} | ", format!("{kind}:"), todo.title));
+    if let Some(due) = &todo.due {
+        push_date_range(due, buf);
+    }
+}
+
+/// A markdown report suitable for pasting into a standup or weekly notes
+pub fn review_markdown(review: &Review) -> String {
+    let mut buf = format!(
+        "# {} ({} – {})\n",
+        review.period.title(),
+        review.from,
+        review.until
+    );
+
+    for (heading, todos) in [
+        ("Completed", &review.completed),
+        ("Slipped", &review.slipped),
+        ("Coming up", &review.upcoming),
+    ] {
+        buf.push_str(&format!("\n## {heading} ({})\n\n", todos.len()));
+        if todos.is_empty() {
+            buf.push_str("Nothing here.\n");
+        }
+        for todo in todos {
+            push_markdown_item(todo, &mut buf);
+        }
+    }
+    buf
+}
+
+fn push_markdown_item(todo: &Todo, buf: &mut String) {
+    let checkbox = if todo.done { "[x]" } else { "[ ]" };
+    match &todo.url {
+        Some(url) => buf.push_str(&format!("- {checkbox} [{}]({url})", todo.title)),
+        None => buf.push_str(&format!("- {checkbox} {}", todo.title)),
+    }
+    if let Some(due) = &todo.due {
+        buf.push_str(" — ");
+        push_date_range(due, buf);
+    }
+    buf.push('\n');
+}
+
+/// A `::warning::` workflow command per overdue todo, which GitHub Actions
+/// shows as an annotation on the run
+pub fn gha_annotations<Tz>(todos: &[Todo], now: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+{
+    let mut buf = String::new();
+    for todo in todos.iter().filter(|todo| todo.is_overdue(now)) {
+        let mut message = todo.title.clone();
+        if let Some(due) = &todo.due {
+            message.push_str(" (due ");
+            push_date_range(due, &mut message);
+            message.push(')');
+        }
+        buf.push_str(&format!(
+            "::warning title={}::{}\n",
+            escape_gha_property("Overdue todo"),
+            escape_gha_data(&message)
+        ));
+    }
+    buf
+}
+
+/// One `LOCATION:1:1: KIND: MESSAGE` line per todo, the shape compilers report
+/// errors in, so editors can list todos like build errors:
+///
+/// - `LOCATION` is the todo's Notion URL, or its page id when it has none,
+///   meant to be opened rather than read as a file
+/// - `KIND` is `warning` when the todo is overdue, `note` when it is done and
+///   `info` otherwise
+/// - `MESSAGE` is the title, followed by ` (due DATE)` when it has a due date
+///
+/// Line breaks in titles become spaces, so every todo stays one line. Vim reads
+/// it with `set errorformat=%f:%l:%c:\ %t%*[a-z]:\ %m`, given `:` is added to
+/// `isfname`.
+pub fn quickfix<Tz>(todos: &[Todo], now: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+{
+    let mut buf = String::new();
+    for todo in todos {
+        let location = todo.url.as_deref().unwrap_or(&todo.id);
+        let kind = if todo.is_overdue(now) {
+            "warning"
+        } else if todo.done {
+            "note"
+        } else {
+            "info"
+        };
+        let mut message = todo.title.replace(['\r', '\n'], " ");
+        if let Some(due) = &todo.due {
+            message.push_str(" (due ");
+            push_date_range(due, &mut message);
+            message.push(')');
+        }
+        buf.push_str(&format!("{location}:1:1: {kind}: {message}\n"));
+    }
+    buf
+}
+
+/// Todo rows a widget has below its header, unless `max_rows` says otherwise
+const WIDGET_ROWS: usize = 10;
+/// Title width of a widget, unless `title_width` says otherwise
+const WIDGET_TITLE_WIDTH: usize = 30;
+/// Fits `YYYY-MM-DD HH:MM`
+const WIDGET_DUE_WIDTH: usize = 16;
+
+/// A header with the open and overdue counts over one row per todo, marked `!`
+/// when overdue and `x` when done, with its due date (the end of a range)
+/// aligned right. Every line is as wide as the others, and blank lines fill
+/// up to `max_rows` rows, so tools re-rendering it on a timer never see the
+/// layout shift. Only `title_width` and `max_rows` of `options` apply.
+pub fn widget<Tz>(todos: &[Todo], options: &TableOptions, now: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+{
+    let rows = options.max_rows.unwrap_or(WIDGET_ROWS);
+    let title_width = options.title_width.unwrap_or(WIDGET_TITLE_WIDTH);
+    let width = 2 + title_width + 1 + WIDGET_DUE_WIDTH;
+
+    let open = todos.iter().filter(|todo| !todo.done).count();
+    let overdue = todos.iter().filter(|todo| todo.is_overdue(now)).count();
+    let mut lines = vec![format!("{open} open, {overdue} overdue")];
+
+    // The last row counts what didn't fit rather than showing one more todo
+    let shown = if todos.len() > rows {
+        rows.saturating_sub(1)
+    } else {
+        todos.len()
+    };
+    for todo in &todos[..shown] {
+        let marker = if todo.is_overdue(now) {
+            '!'
+        } else if todo.done {
+            'x'
+        } else {
+            ' '
+        };
+        let due: String = todo
+            .due
+            .as_ref()
+            .map(|due| datetime::format_datetime(due.end.as_deref().unwrap_or(&due.start)))
+            .unwrap_or_default()
+            .chars()
+            .take(WIDGET_DUE_WIDTH)
+            .collect();
+        let title = truncate(&todo.title.replace(['\r', '\n'], " "), title_width);
+        lines.push(format!(
+            "{marker} {title:title_width$} {due:>WIDGET_DUE_WIDTH$}"
+        ));
+    }
+    if shown < todos.len() {
+        lines.push(format!("…and {} more", todos.len() - shown));
+    }
+    lines.resize(rows + 1, String::new());
+
+    let mut buf = String::new();
+    for line in lines {
+        buf.push_str(&format!("{:width$}\n", truncate(&line, width)));
+    }
+    buf
+}
+
+/// An Alfred Script Filter item per todo, for launcher workflows:
+///
+/// - `arg`, what actioning an item passes on, is the Notion URL, or the page id
+///   when the todo has none
+/// - `subtitle` says whether the todo is overdue, done or open, and when it is
+///   due
+/// - `icon` is `icons/overdue.png`, `icons/done.png` or `icons/open.png`,
+///   relative to the workflow's folder
+/// - the `todo_id` workflow variable holds the page id
+pub fn alfred<Tz>(todos: &[Todo], now: &DateTime<Tz>) -> crate::Result<String>
+where
+    Tz: TimeZone,
+{
+    let items: Vec<Value> = todos
+        .iter()
+        .map(|todo| {
+            let status = if todo.is_overdue(now) {
+                "overdue"
+            } else if todo.done {
+                "done"
+            } else {
+                "open"
+            };
+            let mut subtitle = status[..1].to_uppercase() + &status[1..];
+            if let Some(due) = &todo.due {
+                subtitle.push_str(", due ");
+                push_date_range(due, &mut subtitle);
+            }
+            let arg = todo.url.as_deref().unwrap_or(&todo.id);
+            let mut item = json!({
+                "uid": todo.id,
+                "title": todo.title.replace(['\r', '\n'], " "),
+                "subtitle": subtitle,
+                "arg": arg,
+                "autocomplete": todo.title,
+                "icon": {"path": format!("icons/{status}.png")},
+                "text": {"copy": arg, "largetype": todo.title},
+                "variables": {"todo_id": todo.id},
+            });
+            if let Some(url) = &todo.url {
+                item["quicklookurl"] = json!(url);
+            }
+            item
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&json!({ "items": items }))?)
+}
+
+/// A `TODO` or `DONE` heading per todo, tagged with its tags. A due date becomes
+/// a `DEADLINE`, and a date range is `SCHEDULED` at its start with the
+/// `DEADLINE` at its end. The Notion page id and URL go in the heading's
+/// property drawer.
+pub fn org(todos: &[Todo]) -> String {
+    let mut buf = String::new();
+    for todo in todos {
+        let keyword = if todo.done { "DONE" } else { "TODO" };
+        buf.push_str(&format!(
+            "* {keyword} {}",
+            todo.title.replace(['\r', '\n'], " ")
+        ));
+        if !todo.tags.is_empty() {
+            // Org tags are words, so other characters become underscores
+            let tags: Vec<String> = todo
+                .tags
+                .iter()
+                .map(|tag| {
+                    tag.chars()
+                        .map(|c| {
+                            if c.is_alphanumeric() || "_@#%".contains(c) {
+                                c
+                            } else {
+                                '_'
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            buf.push_str(&format!(" :{}:", tags.join(":")));
+        }
+        buf.push('\n');
+
+        if let Some(due) = &todo.due {
+            let start = datetime::org_timestamp(&due.start);
+            let end = due.end.as_deref().and_then(datetime::org_timestamp);
+            match (start, end) {
+                (Some(start), Some(end)) => {
+                    buf.push_str(&format!("SCHEDULED: {start} DEADLINE: {end}\n"))
+                }
+                (Some(start), None) => buf.push_str(&format!("DEADLINE: {start}\n")),
+                _ => {}
+            }
+        }
+
+        buf.push_str(&format!(":PROPERTIES:\n:NOTION_ID: {}\n", todo.id));
+        if let Some(url) = &todo.url {
+            buf.push_str(&format!(":NOTION_URL: {url}\n"));
+        }
+        buf.push_str(":END:\n");
+    }
+    buf
+}
+
+/// A shell prompt segment: `☐` with the open count, then `⚑` with the overdue
+/// count if any, e.g. `☐3 ⚑1`. Empty when nothing is open, so the prompt only
+/// shows it when there's something to do.
+pub fn prompt<Tz>(todos: &[Todo], now: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+{
+    let open = todos.iter().filter(|todo| !todo.done).count();
+    let overdue = todos.iter().filter(|todo| todo.is_overdue(now)).count();
+    match (open, overdue) {
+        (0, _) => String::new(),
+        (open, 0) => format!("☐{open}\n"),
+        (open, overdue) => format!("☐{open} ⚑{overdue}\n"),
+    }
+}
+
+/// A markdown table for `$GITHUB_STEP_SUMMARY`
+pub fn gha_summary<Tz>(todos: &[Todo], now: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+{
+    let open = todos.iter().filter(|todo| !todo.done).count();
+    let overdue = todos.iter().filter(|todo| todo.is_overdue(now)).count();
+
+    let mut buf = format!("### Todos: {open} open, {overdue} overdue\n\n");
+    buf.push_str("| Status | Title | Due |\n| --- | --- | --- |\n");
+    for todo in todos {
+        let status = if todo.done {
+            "✅ done"
+        } else if todo.is_overdue(now) {
+            "⚠️ overdue"
+        } else {
+            "open"
+        };
+        let mut due = String::new();
+        if let Some(range) = &todo.due {
+            push_date_range(range, &mut due);
+        }
+        buf.push_str(&format!(
+            "| {status} | {} | {due} |\n",
+            todo.title.replace('|', "\\|")
+        ));
+    }
+    buf
+}
+
+fn escape_gha_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_gha_property(s: &str) -> String {
+    escape_gha_data(s).replace(':', "%3A").replace(',', "%2C")
+}
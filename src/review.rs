@@ -0,0 +1,127 @@
+use crate::datetime;
+use crate::todo::Todo;
+
+use chrono::{DateTime, TimeDelta, TimeZone};
+
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    pub fn title(self) -> &'static str {
+        match self {
+            Period::Day => "Daily review",
+            Period::Week => "Weekly review",
+        }
+    }
+
+    fn length(self) -> TimeDelta {
+        match self {
+            Period::Day => TimeDelta::days(1),
+            Period::Week => TimeDelta::weeks(1),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Period, String> {
+        match s {
+            "day" => Ok(Period::Day),
+            "week" => Ok(Period::Week),
+            _ => Err(format!("unknown review period '{s}', expected day or week")),
+        }
+    }
+}
+
+/// A look back over the last period and ahead over the next one, based on due
+/// dates as there is no completion history to go by
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    pub period: Period,
+    /// First and last day covered, as `YYYY-MM-DD`
+    pub from: String,
+    pub until: String,
+    /// Done todos due during the period or later
+    pub completed: Vec<Todo>,
+    /// Open todos whose due date has passed
+    pub slipped: Vec<Todo>,
+    /// Open todos starting before the end of the next period
+    pub upcoming: Vec<Todo>,
+}
+
+impl Review {
+    pub fn new<Tz>(todos: &[Todo], period: Period, now: &DateTime<Tz>) -> Review
+    where
+        Tz: TimeZone,
+        Tz::Offset: std::fmt::Display,
+    {
+        let tz = now.timezone();
+        let since = now.clone() - period.length();
+        let horizon = now.clone() + period.length();
+
+        let mut review = Review {
+            period,
+            from: since.format("%Y-%m-%d").to_string(),
+            until: now.format("%Y-%m-%d").to_string(),
+            completed: Vec::new(),
+            slipped: Vec::new(),
+            upcoming: Vec::new(),
+        };
+
+        for todo in todos {
+            let Some(deadline) = todo.deadline(&tz) else {
+                continue;
+            };
+            let starts_at = todo
+                .due
+                .as_ref()
+                .and_then(|due| datetime::starts_at(&due.start, &tz));
+
+            if todo.done {
+                if deadline >= since {
+                    review.completed.push(todo.clone());
+                }
+            } else if deadline < *now {
+                review.slipped.push(todo.clone());
+            } else if starts_at.is_some_and(|start| start <= horizon) {
+                review.upcoming.push(todo.clone());
+            }
+        }
+
+        review
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_weekly_review() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 3, 12, 0, 0).unwrap();
+        let todos = vec![
+            Todo::sample("done this week", true, Some("2024-05-01")),
+            Todo::sample("done long ago", true, Some("2024-04-01")),
+            Todo::sample("slipped", false, Some("2024-05-02")),
+            Todo::sample("today", false, Some("2024-05-03")),
+            Todo::sample("next week", false, Some("2024-05-10")),
+            Todo::sample("later", false, Some("2024-05-20")),
+        ];
+
+        let review = Review::new(&todos, Period::Week, &now);
+
+        let titles = |todos: &[Todo]| todos.iter().map(|t| t.title.clone()).collect::<Vec<_>>();
+        assert_eq!(review.from, "2024-04-26");
+        assert_eq!(review.until, "2024-05-03");
+        assert_eq!(titles(&review.completed), ["done this week"]);
+        assert_eq!(titles(&review.slipped), ["slipped"]);
+        assert_eq!(titles(&review.upcoming), ["today", "next week"]);
+    }
+}
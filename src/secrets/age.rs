@@ -0,0 +1,49 @@
+use age::armor::ArmoredReader;
+use age::{Decryptor, IdentityFile};
+
+use std::io::Read;
+use std::path::Path;
+
+/// Decrypts an age-encrypted, possibly armored, secrets file with the
+/// identities in `identity_path`, e.g. one made by `age-keygen` or `rage-keygen`
+pub fn decrypt(ciphertext: &[u8], identity_path: &Path) -> crate::Result<Vec<u8>> {
+    let identities = IdentityFile::from_file(identity_path.to_string_lossy().into_owned())
+        .map_err(|e| format!("failed to read '{}': {e}", identity_path.display()))?
+        .into_identities()?;
+
+    let decryptor = Decryptor::new_buffered(ArmoredReader::new(ciphertext))?;
+    let mut reader = decryptor.decrypt(identities.iter().map(|identity| identity.as_ref() as _))?;
+    let mut plaintext = Vec::new();
+    reader.read_to_end(&mut plaintext)?;
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+    use age::x25519;
+
+    #[test]
+    fn test_decrypt() {
+        let identity = x25519::Identity::generate();
+        let dir = tempfile::tempdir().unwrap();
+        let identity_path = dir.path().join("key.txt");
+        std::fs::write(&identity_path, identity.to_string().expose_secret()).unwrap();
+
+        let plaintext = b"DB_URL=http://localhost:1234\nAPI_KEY=myapikey";
+        let ciphertext = age::encrypt(&identity.to_public(), plaintext).unwrap();
+        let armored = age::encrypt_and_armor(&identity.to_public(), plaintext).unwrap();
+
+        assert_eq!(decrypt(&ciphertext, &identity_path).unwrap(), plaintext);
+        assert_eq!(
+            decrypt(armored.as_bytes(), &identity_path).unwrap(),
+            plaintext
+        );
+
+        let other = dir.path().join("other.txt");
+        let other_identity = x25519::Identity::generate();
+        std::fs::write(&other, other_identity.to_string().expose_secret()).unwrap();
+        assert!(decrypt(&ciphertext, &other).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! The API key kept in the OS keychain: the macOS Keychain, the Secret Service
+//! on Linux, or the Windows Credential Manager
+
+use keyring::{Entry, Error};
+
+const SERVICE: &str = "notifieru";
+const USER: &str = "api_key";
+
+fn entry() -> crate::Result<Entry> {
+    Entry::new(SERVICE, USER).map_err(|e| format!("failed to open the keychain: {e}").into())
+}
+
+/// The stored API key, if any
+pub fn api_key() -> crate::Result<Option<String>> {
+    match entry()?.get_password() {
+        Ok(api_key) => Ok(Some(api_key)),
+        Err(Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("failed to read the API key from the keychain: {e}").into()),
+    }
+}
+
+/// Stores the API key, replacing an earlier one
+pub fn set_api_key(api_key: &str) -> crate::Result<()> {
+    entry()?
+        .set_password(api_key)
+        .map_err(|e| format!("failed to write the API key to the keychain: {e}").into())
+}
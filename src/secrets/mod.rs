@@ -0,0 +1,487 @@
+#[cfg(feature = "age")]
+pub mod age;
+#[cfg(feature = "keyring")]
+pub mod keyring;
+
+use crate::config::Config;
+use crate::Error;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::{Path, PathBuf};
+
+const FILEPATH_PLACEHOLDER: &str = "<secrets_file>";
+const KEY_VAL_DELIM: char = '=';
+
+/// Takes precedence over `DB_URL` in the secrets file
+pub const DB_URL_ENV: &str = "NOTIFIERU_DB_URL";
+/// Takes precedence over `API_KEY` in the secrets file
+pub const API_KEY_ENV: &str = "NOTIFIERU_API_KEY";
+/// Takes precedence over `age_identity` in the config file
+pub const AGE_IDENTITY_ENV: &str = "NOTIFIERU_AGE_IDENTITY";
+
+/// What every age-encrypted file starts with, in binary and armored form
+const AGE_HEADERS: [&[u8]; 2] = [
+    b"age-encryption.org/",
+    b"-----BEGIN AGE ENCRYPTED FILE-----",
+];
+
+#[derive(Debug)]
+pub struct Secret {
+    pub db_url: String,
+    pub api_key: String,
+    /// Other keys in the secrets file, e.g. when it doubles as a project's
+    /// `.env`
+    pub extra: HashMap<String, String>,
+}
+
+/// Where to read the API key from instead, for wrapper scripts and secret
+/// managers, e.g. `pass notion | notifieru --api-key-stdin`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiKeySource {
+    Stdin,
+    /// Also takes file descriptors as `/dev/fd/<n>`
+    File(PathBuf),
+}
+
+impl ApiKeySource {
+    pub fn read(&self) -> crate::Result<String> {
+        let (key, from) = match self {
+            ApiKeySource::Stdin => {
+                let mut key = String::new();
+                io::stdin().read_to_string(&mut key)?;
+                (key, "stdin".to_owned())
+            }
+            ApiKeySource::File(path) => {
+                let key = fs::read_to_string(path)
+                    .map_err(|e| format!("failed to read '{}': {e}", path.display()))?;
+                (key, format!("'{}'", path.display()))
+            }
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("no API key on {from}").into());
+        }
+        Ok(key.to_owned())
+    }
+}
+
+/// Values set through the environment, e.g. in CI or a container, which take
+/// precedence over the secrets file
+#[derive(Debug, Default)]
+struct Overrides {
+    db_url: Option<String>,
+    api_key: Option<String>,
+    /// Look the API key up in the OS keychain when nothing else provides it
+    keychain: bool,
+    /// Decrypts an age-encrypted secrets file
+    age_identity: Option<PathBuf>,
+}
+
+impl Overrides {
+    fn from_env() -> Overrides {
+        // An empty variable counts as unset
+        let var = |name| {
+            let value = env::var(name).ok()?;
+            Some(value.trim().to_owned()).filter(|value| !value.is_empty())
+        };
+        Overrides {
+            db_url: var(DB_URL_ENV),
+            api_key: var(API_KEY_ENV),
+            keychain: cfg!(feature = "keyring"),
+            age_identity: var(AGE_IDENTITY_ENV).map(PathBuf::from),
+        }
+    }
+
+    /// The keychain is the last resort, so that an unreachable one (e.g. no
+    /// Secret Service in a container) only matters when it's actually needed
+    fn keychain_api_key(&self) -> crate::Result<Option<String>> {
+        if !self.keychain {
+            return Ok(None);
+        }
+        #[cfg(feature = "keyring")]
+        return keyring::api_key();
+        #[cfg(not(feature = "keyring"))]
+        Ok(None)
+    }
+}
+
+/// Reads the secrets file, with `NOTIFIERU_DB_URL` and `NOTIFIERU_API_KEY`
+/// taking precedence over its values. `read_secrets_with` also takes an API
+/// key source, which wins over both. Builds with the `keyring` feature fall
+/// back to the OS keychain for the API key. The file is not needed when the
+/// values are found elsewhere.
+///
+/// Builds with the `age` feature also read age-encrypted files, decrypting
+/// them with the identity file `NOTIFIERU_AGE_IDENTITY` points at.
+pub fn read_secrets<T>(secrets_path: T) -> crate::Result<Secret>
+where
+    T: AsRef<Path>,
+{
+    read_secrets_with(secrets_path, &Config::default(), None)
+}
+
+/// Like `read_secrets`, with the config file's `database_url` standing in for
+/// `DB_URL`: it loses to `NOTIFIERU_DB_URL` but wins over the file, which may
+/// then leave `DB_URL` out. Its `age_identity` likewise loses to
+/// `NOTIFIERU_AGE_IDENTITY`. `api_key`, when given, is read and wins over
+/// every other source of the API key.
+pub fn read_secrets_with<T>(
+    secrets_path: T,
+    config: &Config,
+    api_key: Option<&ApiKeySource>,
+) -> crate::Result<Secret>
+where
+    T: AsRef<Path>,
+{
+    find_secrets(secrets_path.as_ref(), config, api_key).map_err(|e| match e {
+        Error::Secrets(_) => e,
+        e => Error::Secrets(e.to_string()),
+    })
+}
+
+fn find_secrets(
+    secrets_path: &Path,
+    config: &Config,
+    api_key: Option<&ApiKeySource>,
+) -> crate::Result<Secret> {
+    let mut overrides = Overrides::from_env();
+    if let Some(source) = api_key {
+        overrides.api_key = Some(source.read()?);
+    }
+    overrides.db_url = overrides.db_url.or_else(|| config.database_url.clone());
+    overrides.age_identity = overrides
+        .age_identity
+        .or_else(|| config.age_identity.clone());
+    if let Overrides {
+        db_url: Some(db_url),
+        api_key: Some(api_key),
+        ..
+    } = overrides
+    {
+        return Ok(Secret {
+            db_url,
+            api_key,
+            extra: HashMap::new(),
+        });
+    }
+
+    let file_name = secrets_path.to_str().unwrap_or(FILEPATH_PLACEHOLDER);
+
+    match fs::read(secrets_path) {
+        Ok(content) => {
+            let content = decrypt_secrets(content, file_name, overrides.age_identity.as_deref())?;
+            parse_secrets(content.as_slice(), file_name, overrides)
+        }
+        Err(_) => {
+            let not_found = || {
+                format!("'{file_name}' path not found, and {DB_URL_ENV} and {API_KEY_ENV} are not both set")
+            };
+            let Some(db_url) = overrides.db_url.clone() else {
+                return Err(not_found().into());
+            };
+            let api_key = overrides.keychain_api_key()?.ok_or_else(not_found)?;
+            Ok(Secret {
+                db_url,
+                api_key,
+                extra: HashMap::new(),
+            })
+        }
+    }
+}
+
+/// Decrypts `content` if it's age-encrypted, or hands it back as is
+fn decrypt_secrets(
+    content: Vec<u8>,
+    file_name: &str,
+    age_identity: Option<&Path>,
+) -> crate::Result<Vec<u8>> {
+    if !AGE_HEADERS.iter().any(|header| content.starts_with(header)) {
+        return Ok(content);
+    }
+    #[cfg(feature = "age")]
+    {
+        let Some(age_identity) = age_identity else {
+            return Err(format!(
+                "'{file_name}' is age-encrypted, set {AGE_IDENTITY_ENV} or age_identity in the config file"
+            )
+            .into());
+        };
+        age::decrypt(&content, age_identity)
+            .map_err(|e| format!("failed to decrypt '{file_name}': {e}").into())
+    }
+    #[cfg(not(feature = "age"))]
+    {
+        let _ = age_identity;
+        Err(format!("'{file_name}' is age-encrypted, rebuild with the age feature").into())
+    }
+}
+
+/// The actual parsing part lives here for testability. The file follows the
+/// `.env` conventions: blank lines and `#` comments are skipped, keys may be
+/// prefixed with `export`, and values may be single or double quoted.
+fn parse_secrets<R>(file: R, file_name: &str, overrides: Overrides) -> crate::Result<Secret>
+where
+    R: BufRead,
+{
+    let mut db_url: Option<String> = None;
+    let mut api_key: Option<String> = None;
+    let mut extra = HashMap::new();
+
+    for (i, line) in file.lines().enumerate() {
+        let i = i + 1;
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        match line.split_once(KEY_VAL_DELIM) {
+            Some((key, value)) => match key.trim() {
+                "DB_URL" => db_url = Some(check_val_empty(value, file_name, i)?),
+                "API_KEY" => api_key = Some(check_val_empty(value, file_name, i)?),
+                key => {
+                    extra.insert(key.to_owned(), unquote(value.trim()).to_owned());
+                }
+            },
+            None => return Err(format!("invalid line format at {file_name}:{i}").into()),
+        }
+    }
+
+    let db_url = overrides
+        .db_url
+        .clone()
+        .or(db_url)
+        .ok_or_else(|| format!("DB_URL value not found in {file_name}"))?;
+    let api_key = match overrides.api_key.clone().or(api_key) {
+        Some(api_key) => api_key,
+        None => overrides
+            .keychain_api_key()?
+            .ok_or_else(|| format!("API_KEY value not found in {file_name}"))?,
+    };
+
+    Ok(Secret {
+        db_url,
+        api_key,
+        extra,
+    })
+}
+
+fn check_val_empty(value: &str, file: &str, line: usize) -> crate::Result<String> {
+    let value = unquote(value.trim());
+    if value.is_empty() {
+        return Err(format!("value is empty at {file}:{line}").into());
+    }
+    Ok(value.to_owned())
+}
+
+/// Strips one pair of matching quotes around `value`
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| {
+            value
+                .strip_prefix(quote)
+                .and_then(|value| value.strip_suffix(quote))
+        })
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mock_file(contents: &str) -> Cursor<Vec<u8>> {
+        Cursor::new(contents.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_read_secrets_success() {
+        let file_content = "DB_URL=http://localhost:1234
+API_KEY=myapikey";
+        let file = mock_file(file_content);
+
+        // Read secrets (passing the mock file)
+        let result = parse_secrets(file, FILEPATH_PLACEHOLDER, Overrides::default());
+
+        assert!(result.is_ok());
+        let Secret {
+            db_url, api_key, ..
+        } = result.unwrap();
+        assert_eq!(db_url, "http://localhost:1234");
+        assert_eq!(api_key, "myapikey");
+    }
+
+    #[test]
+    fn test_read_secrets_missing_db_url() {
+        let file_content = r"API_KEY=myapikey";
+        let file = mock_file(file_content);
+
+        // Read secrets and assert error for missing DB_URL
+        let result = parse_secrets(file, FILEPATH_PLACEHOLDER, Overrides::default());
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "DB_URL value not found in <secrets_file>"
+        );
+    }
+
+    #[test]
+    fn test_read_secrets_missing_api_key() {
+        let file_content = r"DB_URL=http://localhost:1234";
+        let file = mock_file(file_content);
+
+        // Read secrets and assert error for missing API_KEY
+        let result = parse_secrets(file, FILEPATH_PLACEHOLDER, Overrides::default());
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "API_KEY value not found in <secrets_file>"
+        );
+    }
+
+    #[test]
+    fn test_read_secrets_extra_keys() {
+        let file_content = r"DB_URL=http://localhost:1234
+OTHER_KEY=value
+API_KEY=myapikey";
+        let file = mock_file(file_content);
+
+        // Keys notifieru doesn't use are kept rather than rejected
+        let secret = parse_secrets(file, FILEPATH_PLACEHOLDER, Overrides::default()).unwrap();
+
+        assert_eq!(secret.api_key, "myapikey");
+        assert_eq!(secret.extra["OTHER_KEY"], "value");
+        assert_eq!(secret.extra.len(), 1);
+    }
+
+    #[test]
+    fn test_read_secrets_dotenv_syntax() {
+        let file_content = r#"# Notion
+export DB_URL="http://localhost:1234"
+
+  API_KEY = 'my api key'
+EMPTY=
+"#;
+        let file = mock_file(file_content);
+
+        let secret = parse_secrets(file, FILEPATH_PLACEHOLDER, Overrides::default()).unwrap();
+
+        assert_eq!(secret.db_url, "http://localhost:1234");
+        assert_eq!(secret.api_key, "my api key");
+        assert_eq!(secret.extra["EMPTY"], "");
+    }
+
+    #[test]
+    fn test_read_secrets_invalid_line_format() {
+        let file_content = r"DB_URL=http://localhost:1234
+API_KEY:myapikey";
+        let file = mock_file(file_content);
+
+        // Read secrets and assert error for invalid line format
+        let result = parse_secrets(file, FILEPATH_PLACEHOLDER, Overrides::default());
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid line format at <secrets_file>:2"
+        );
+    }
+
+    #[test]
+    fn test_read_secrets_empty_value() {
+        let file_content = r"DB_URL=
+API_KEY=myapikey";
+        let file = mock_file(file_content);
+
+        // Read secrets and assert error for empty DB_URL value
+        let result = parse_secrets(file, FILEPATH_PLACEHOLDER, Overrides::default());
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "value is empty at <secrets_file>:1"
+        );
+    }
+
+    #[test]
+    fn test_read_secrets_overrides() {
+        let file_content = r"DB_URL=http://localhost:1234";
+        let file = mock_file(file_content);
+        let overrides = Overrides {
+            db_url: Some("http://localhost:5678".to_owned()),
+            api_key: Some("envapikey".to_owned()),
+            ..Overrides::default()
+        };
+
+        // Overrides win, and fill in what the file lacks
+        let Secret {
+            db_url, api_key, ..
+        } = parse_secrets(file, FILEPATH_PLACEHOLDER, overrides).unwrap();
+
+        assert_eq!(db_url, "http://localhost:5678");
+        assert_eq!(api_key, "envapikey");
+    }
+
+    #[test]
+    fn test_read_secrets_db_url_override() {
+        let file_content = r"API_KEY=myapikey";
+        let file = mock_file(file_content);
+        let overrides = Overrides {
+            db_url: Some("http://localhost:5678".to_owned()),
+            ..Overrides::default()
+        };
+
+        // The override stands in for a missing DB_URL
+        let Secret {
+            db_url, api_key, ..
+        } = parse_secrets(file, FILEPATH_PLACEHOLDER, overrides).unwrap();
+
+        assert_eq!(db_url, "http://localhost:5678");
+        assert_eq!(api_key, "myapikey");
+    }
+
+    #[test]
+    fn test_api_key_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token");
+
+        std::fs::write(&path, "filekey\n").unwrap();
+        assert_eq!(ApiKeySource::File(path.clone()).read().unwrap(), "filekey");
+
+        std::fs::write(&path, "\n").unwrap();
+        assert!(ApiKeySource::File(path.clone())
+            .read()
+            .unwrap_err()
+            .to_string()
+            .starts_with("no API key on '"));
+        assert!(ApiKeySource::File(dir.path().join("missing"))
+            .read()
+            .is_err());
+    }
+
+    #[test]
+    fn test_decrypt_secrets() {
+        let plaintext = b"DB_URL=http://localhost:1234".to_vec();
+        assert_eq!(
+            decrypt_secrets(plaintext.clone(), FILEPATH_PLACEHOLDER, None).unwrap(),
+            plaintext
+        );
+
+        // Encrypted files can't be read without an identity
+        let encrypted = b"age-encryption.org/v1\n-> X25519 ...".to_vec();
+        let result = decrypt_secrets(encrypted, FILEPATH_PLACEHOLDER, None);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("'<secrets_file>' is age-encrypted"));
+    }
+
+    // It's quite difficult to consistently test the case where the file is not found...
+}
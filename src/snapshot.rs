@@ -0,0 +1,127 @@
+use crate::todo::Todo;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever a change to the format would break older readers
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The on-disk and machine-readable representation of a fetched todo list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    pub todos: Vec<Todo>,
+}
+
+impl Snapshot {
+    pub fn new(todos: Vec<Todo>) -> Snapshot {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            created_at,
+            todos,
+        }
+    }
+
+    pub fn from_reader<R>(reader: R) -> crate::Result<Snapshot>
+    where
+        R: Read,
+    {
+        let snapshot: Snapshot = serde_json::from_reader(reader)?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(format!(
+                "snapshot version {} is newer than the supported version {SNAPSHOT_VERSION}",
+                snapshot.version
+            )
+            .into());
+        }
+        Ok(snapshot)
+    }
+
+    pub fn to_writer<W>(&self, writer: W) -> crate::Result<()>
+    where
+        W: Write,
+    {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    pub fn load<T>(path: T) -> crate::Result<Snapshot>
+    where
+        T: AsRef<Path>,
+    {
+        let file = fs::File::open(&path)
+            .map_err(|e| format!("failed to open '{}': {e}", path.as_ref().display()))?;
+        Snapshot::from_reader(io::BufReader::new(file))
+    }
+
+    pub fn save<T>(&self, path: T) -> crate::Result<()>
+    where
+        T: AsRef<Path>,
+    {
+        let file = fs::File::create(&path)
+            .map_err(|e| format!("failed to create '{}': {e}", path.as_ref().display()))?;
+        self.to_writer(io::BufWriter::new(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::todo::Source;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let snapshot = Snapshot::new(vec![Todo {
+            id: "page-1".to_owned(),
+            tags: vec!["work".to_owned()],
+            priority: Some("High".to_owned()),
+            ..Todo::sample("Write report", false, Some("2024-05-01T09:00:00.000+09:00"))
+        }]);
+
+        let mut buf = Vec::new();
+        snapshot.to_writer(&mut buf).unwrap();
+        let loaded = Snapshot::from_reader(buf.as_slice()).unwrap();
+
+        assert_eq!(loaded, snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_missing_optional_fields() {
+        let json = r#"{
+            "version": 1,
+            "created_at": 0,
+            "todos": [{"id": "page-1", "title": "Call mom", "done": true}]
+        }"#;
+
+        let snapshot = Snapshot::from_reader(json.as_bytes()).unwrap();
+
+        let todo = &snapshot.todos[0];
+        assert_eq!(todo.due, None);
+        assert!(todo.tags.is_empty());
+        assert_eq!(todo.source, Source::Notion);
+    }
+
+    #[test]
+    fn test_snapshot_newer_version() {
+        let json = r#"{"version": 999, "created_at": 0, "todos": []}"#;
+
+        let result = Snapshot::from_reader(json.as_bytes());
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "snapshot version 999 is newer than the supported version 1"
+        );
+    }
+}
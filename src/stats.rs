@@ -0,0 +1,169 @@
+use crate::todo::Todo;
+
+use chrono::{DateTime, TimeDelta, TimeZone};
+use serde_json::{json, Value};
+
+/// How many upcoming todos a summary lists
+const UPCOMING_LIMIT: usize = 5;
+
+/// Marks the callout block `stats --publish` owns, so it can be found and
+/// replaced on the next publish
+pub const PUBLISHED_MARKER: &str = "notifieru stats";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub total: usize,
+    pub done: usize,
+    pub overdue: usize,
+    /// Open todos starting within the next week, soonest first
+    pub upcoming: Vec<Todo>,
+}
+
+impl Stats {
+    pub fn new<Tz>(todos: &[Todo], now: &DateTime<Tz>) -> Stats
+    where
+        Tz: TimeZone,
+    {
+        let tz = now.timezone();
+        let horizon = now.clone() + TimeDelta::weeks(1);
+
+        let mut upcoming: Vec<_> = todos
+            .iter()
+            .filter(|todo| !todo.done && !todo.is_overdue(now))
+            .filter_map(|todo| {
+                let start = crate::datetime::starts_at(&todo.due.as_ref()?.start, &tz)?;
+                (start <= horizon).then_some((start, todo))
+            })
+            .collect();
+        upcoming.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Stats {
+            total: todos.len(),
+            done: todos.iter().filter(|todo| todo.done).count(),
+            overdue: todos.iter().filter(|todo| todo.is_overdue(now)).count(),
+            upcoming: upcoming
+                .into_iter()
+                .take(UPCOMING_LIMIT)
+                .map(|(_, todo)| todo.clone())
+                .collect(),
+        }
+    }
+
+    pub fn open(&self) -> usize {
+        self.total - self.done
+    }
+
+    /// Percentage of todos done, 0 for an empty database
+    pub fn completion_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.done as f64 * 100.0 / self.total as f64
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} todos: {} done, {} open, {} overdue ({:.0}% complete)",
+            self.total,
+            self.done,
+            self.open(),
+            self.overdue,
+            self.completion_rate()
+        )
+    }
+
+    /// The Notion blocks `stats --publish` writes: one callout holding the
+    /// summary, with the upcoming todos as bulleted children
+    pub fn to_blocks(&self, updated_at: &str) -> Vec<Value> {
+        let upcoming: Vec<_> = self
+            .upcoming
+            .iter()
+            .map(|todo| {
+                let due = todo
+                    .due
+                    .as_ref()
+                    .map(|due| due.start.as_str())
+                    .unwrap_or("");
+                json!({
+                    "object": "block",
+                    "type": "bulleted_list_item",
+                    "bulleted_list_item": {"rich_text": [text(&format!("{} — {due}", todo.title))]}
+                })
+            })
+            .collect();
+
+        vec![json!({
+            "object": "block",
+            "type": "callout",
+            "callout": {
+                "rich_text": [text(&format!(
+                    "{PUBLISHED_MARKER} · updated {updated_at}\n{}",
+                    self.summary()
+                ))],
+                "icon": {"type": "emoji", "emoji": "📊"},
+                "children": upcoming
+            }
+        })]
+    }
+}
+
+/// Whether `block` is a callout written by an earlier publish
+pub fn is_published_block(block: &Value) -> bool {
+    block["type"] == "callout"
+        && block["callout"]["rich_text"][0]["plain_text"]
+            .as_str()
+            .is_some_and(|text| text.starts_with(PUBLISHED_MARKER))
+}
+
+fn text(content: &str) -> Value {
+    json!({"type": "text", "text": {"content": content}})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_stats() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 3, 12, 0, 0).unwrap();
+        let todos = vec![
+            Todo::sample("done", true, Some("2024-05-01")),
+            Todo::sample("overdue", false, Some("2024-05-01")),
+            Todo::sample("in a week", false, Some("2024-05-09")),
+            Todo::sample("tomorrow", false, Some("2024-05-04")),
+            Todo::sample("someday", false, None),
+        ];
+
+        let stats = Stats::new(&todos, &now);
+
+        assert_eq!(stats.total, 5);
+        assert_eq!(stats.open(), 4);
+        assert_eq!(stats.overdue, 1);
+        assert_eq!(
+            stats
+                .upcoming
+                .iter()
+                .map(|t| t.title.as_str())
+                .collect::<Vec<_>>(),
+            ["tomorrow", "in a week"]
+        );
+        assert_eq!(
+            stats.summary(),
+            "5 todos: 1 done, 4 open, 1 overdue (20% complete)"
+        );
+    }
+
+    #[test]
+    fn test_published_block_roundtrip() {
+        let stats = Stats::new(&[], &Utc::now());
+        let mut block = stats.to_blocks("2024-05-03 12:00").remove(0);
+        // Notion adds plain_text to rich text it returns
+        block["callout"]["rich_text"][0]["plain_text"] =
+            block["callout"]["rich_text"][0]["text"]["content"].clone();
+
+        assert!(is_published_block(&block));
+        assert!(!is_published_block(&json!({"type": "paragraph"})));
+        assert_eq!(stats.completion_rate(), 0.0);
+    }
+}
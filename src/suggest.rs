@@ -0,0 +1,53 @@
+/// The option closest to `name`, ignoring case, for a "did you mean" hint on
+/// a typo. Options further than a third of their length away don't count, so
+/// an unrelated name gets no suggestion.
+pub fn closest<'a, I>(name: &str, options: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let name = name.to_lowercase();
+    options
+        .into_iter()
+        .map(|option| (distance(&name, &option.to_lowercase()), option))
+        .filter(|(distance, option)| *distance <= (option.chars().count() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, option)| option)
+}
+
+/// The Levenshtein distance between `a` and `b`, in characters
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest() {
+        let options = ["Not started", "In progress", "Completed"];
+
+        assert_eq!(closest("Complete", options), Some("Completed"));
+        assert_eq!(closest("in progres", options), Some("In progress"));
+        assert_eq!(closest("Done", options), None);
+        assert_eq!(closest("wrk", ["work", "home"]), Some("work"));
+        assert_eq!(closest("x", []), None);
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("same", "same"), 0);
+    }
+}
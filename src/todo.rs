@@ -0,0 +1,757 @@
+use crate::datetime;
+use crate::notion::{Page, Property};
+use crate::suggest;
+use crate::Error;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The built-in fields, by the names `Todo::property` and table columns use
+pub const FIELDS: [&str; 5] = ["title", "due", "done", "tags", "priority"];
+
+/// A todo as notifieru sees it, independent of where it was fetched from.
+///
+/// Fields added after the first snapshot version must be `#[serde(default)]`
+/// so older snapshots keep loading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Todo {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub due: Option<DateRange>,
+    pub done: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub source: Source,
+    /// The properties named in `PropertyNames::extra`, by their Notion name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, PropertyValue>,
+}
+
+/// ISO 8601 dates or datetimes, kept as Notion sent them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateRange {
+    pub start: String,
+    #[serde(default)]
+    pub end: Option<String>,
+}
+
+/// Names of the Notion properties each todo field is read from
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PropertyNames {
+    pub title: String,
+    pub done: String,
+    pub due: String,
+    pub tags: String,
+    pub priority: String,
+    /// A date property set when a todo is done, for `show_done` durations
+    pub completed: String,
+    /// A status or select property, for `--status`
+    pub status: String,
+    /// Further properties to read, e.g. to `--sort` or `--filter` by
+    pub extra: Vec<String>,
+    /// Set from the config file's `done_when`, see `Config::property_names`
+    #[serde(skip)]
+    pub done_when: DoneWhen,
+}
+
+/// Status options that mark a todo done, for databases where the `done`
+/// property is a status (or select) rather than a checkbox
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DoneWhen {
+    pub status: Vec<String>,
+}
+
+impl DoneWhen {
+    /// The type the `done` property is expected to be
+    pub fn kind(&self) -> &'static str {
+        if self.status.is_empty() {
+            "checkbox"
+        } else {
+            "status"
+        }
+    }
+}
+
+impl Default for PropertyNames {
+    fn default() -> PropertyNames {
+        PropertyNames {
+            title: "Name".to_owned(),
+            done: "Done".to_owned(),
+            due: "Due".to_owned(),
+            tags: "Tags".to_owned(),
+            priority: "Priority".to_owned(),
+            completed: "Completed".to_owned(),
+            status: "Status".to_owned(),
+            extra: Vec::new(),
+            done_when: DoneWhen::default(),
+        }
+    }
+}
+
+impl PropertyNames {
+    /// Maps the built-in field `field`, e.g. `due`, to the property `name`
+    pub fn set(&mut self, field: &str, name: &str) -> Result<(), String> {
+        let slot = match field {
+            "title" => &mut self.title,
+            "done" => &mut self.done,
+            "due" => &mut self.due,
+            "tags" => &mut self.tags,
+            "priority" => &mut self.priority,
+            "completed" => &mut self.completed,
+            "status" => &mut self.status,
+            _ => {
+                return Err(format!(
+                "unknown field '{field}', expected title, done, due, tags, priority, completed or status"
+            ))
+            }
+        };
+        *slot = name.to_owned();
+        Ok(())
+    }
+
+    /// Whether the built-in fields keep their default property names, however
+    /// many `extra` properties are read besides
+    pub fn maps_defaults(&self) -> bool {
+        let fields = PropertyNames {
+            extra: Vec::new(),
+            ..self.clone()
+        };
+        fields == PropertyNames::default()
+    }
+
+    /// Describes what keeps todos from being read from a database with the
+    /// given property schema, or nothing if they can be. Tags and priority
+    /// may be missing, the other properties must exist with their type, and
+    /// `done_when` must name options of the done property.
+    pub fn check_schema(&self, properties: &Value) -> Vec<String> {
+        let expected = [
+            (&self.title, "title", true, "title"),
+            (&self.done, self.done_when.kind(), true, "done"),
+            (&self.due, "date", true, "due"),
+            (&self.tags, "multi_select", false, "tags"),
+            (&self.priority, "select", false, "priority"),
+        ];
+
+        let mut problems = Vec::new();
+        for (name, kind, required, key) in expected {
+            // A select works as well as a status for done_when
+            let fits = |actual: &str| actual == kind || (kind == "status" && actual == "select");
+            match properties[name.as_str()]["type"].as_str() {
+                Some(actual) if !fits(actual) => problems.push(format!(
+                    "'{name}' is a {actual} property, expected {kind}; point properties.{key} in the config file at a {kind} property"
+                )),
+                None if required => problems.push(format!(
+                    "'{name}' is missing; rename the {kind} property or set properties.{key} in the config file"
+                )),
+                _ => {}
+            }
+        }
+        for name in &self.extra {
+            if properties.get(name).is_none() {
+                problems.push(format!(
+                    "'{name}' is missing; rename the property or drop it from properties.extra"
+                ));
+            }
+        }
+
+        let done = &properties[self.done.as_str()];
+        let options: Vec<&str> = done["type"]
+            .as_str()
+            .and_then(|kind| done[kind]["options"].as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|option| option["name"].as_str())
+            .collect();
+        let unknown = self
+            .done_when
+            .status
+            .iter()
+            .filter(|status| !options.is_empty() && !options.contains(&status.as_str()));
+        for status in unknown {
+            let fix = match suggest::closest(status, options.iter().copied()) {
+                Some(option) => format!("did you mean '{option}' in done_when.status?"),
+                None => format!("list one of {} in done_when.status", options.join(", ")),
+            };
+            problems.push(format!(
+                "'{status}' is not an option of '{}'; {fix}",
+                self.done
+            ));
+        }
+        problems
+    }
+}
+
+/// A property value, typed so that numbers and dates compare as such rather
+/// than as text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyValue {
+    Checkbox(bool),
+    Number(f64),
+    /// ISO 8601 date or datetime
+    Date(String),
+    /// Titles, rich text, selects, statuses, multi-selects and people
+    Text(String),
+}
+
+impl PropertyValue {
+    /// Reads a Notion property, or `None` when it's empty or of a type that
+    /// has no meaningful order, e.g. files. Multi-selects and people become
+    /// their names, joined by commas.
+    pub fn from_notion(property: &Property) -> Option<PropertyValue> {
+        match property {
+            Property::Checkbox(checked) => Some(PropertyValue::Checkbox(*checked)),
+            Property::Number(number) => number.map(PropertyValue::Number),
+            Property::Date(date) => date
+                .as_ref()
+                .map(|date| PropertyValue::Date(date.start.clone())),
+            Property::Select(option) | Property::Status(option) => option
+                .as_ref()
+                .map(|option| PropertyValue::Text(option.name.clone())),
+            Property::Title(_) | Property::RichText(_) => {
+                property.plain_text().map(PropertyValue::Text)
+            }
+            Property::MultiSelect(options) => {
+                list(options.iter().map(|option| option.name.as_str()))
+            }
+            Property::People(users) => list(users.iter().filter_map(|user| user.name.as_deref())),
+            Property::Other(_) | Property::Invalid(_) => None,
+        }
+    }
+
+    /// Parses `s` as a value of the same type, e.g. a filter's operand
+    pub fn parse_like(&self, s: &str) -> Option<PropertyValue> {
+        match self {
+            PropertyValue::Checkbox(_) => s.parse().ok().map(PropertyValue::Checkbox),
+            PropertyValue::Number(_) => s.parse().ok().map(PropertyValue::Number),
+            PropertyValue::Date(_) => datetime::starts_at(s, &Utc)
+                .is_some()
+                .then(|| PropertyValue::Date(s.to_owned())),
+            PropertyValue::Text(_) => Some(PropertyValue::Text(s.to_owned())),
+        }
+    }
+
+    /// Orders values of the same type; values of different types don't compare
+    pub fn compare(&self, other: &PropertyValue) -> Option<Ordering> {
+        match (self, other) {
+            (PropertyValue::Checkbox(a), PropertyValue::Checkbox(b)) => Some(a.cmp(b)),
+            (PropertyValue::Number(a), PropertyValue::Number(b)) => a.partial_cmp(b),
+            (PropertyValue::Date(a), PropertyValue::Date(b)) => {
+                datetime::starts_at(a, &Utc).partial_cmp(&datetime::starts_at(b, &Utc))
+            }
+            (PropertyValue::Text(a), PropertyValue::Text(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyValue::Checkbox(checked) => write!(f, "[{}]", if *checked { "x" } else { " " }),
+            PropertyValue::Number(number) => write!(f, "{number}"),
+            PropertyValue::Date(date) => write!(f, "{}", datetime::format_datetime(date)),
+            PropertyValue::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// `names` joined by commas, or `None` when there are none
+fn list<'a>(names: impl Iterator<Item = &'a str>) -> Option<PropertyValue> {
+    let names: Vec<_> = names.collect();
+    (!names.is_empty()).then(|| PropertyValue::Text(names.join(", ")))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    #[default]
+    Notion,
+}
+
+impl Todo {
+    /// When the todo is due: the end of its date range, or the start if the
+    /// range has no end
+    pub fn deadline<Tz>(&self, tz: &Tz) -> Option<DateTime<Tz>>
+    where
+        Tz: TimeZone,
+    {
+        let due = self.due.as_ref()?;
+        datetime::deadline(due.end.as_deref().unwrap_or(&due.start), tz)
+    }
+
+    pub fn is_overdue<Tz>(&self, now: &DateTime<Tz>) -> bool
+    where
+        Tz: TimeZone,
+    {
+        !self.done && self.deadline(&now.timezone()).is_some_and(|d| d < *now)
+    }
+
+    /// The value of a built-in field (`title`, `due`, `done`, `tags`, joined
+    /// like a multi-select, or `priority`), or else of a property read
+    /// through `PropertyNames::extra`
+    pub fn property(&self, name: &str) -> Option<PropertyValue> {
+        match name {
+            "title" => Some(PropertyValue::Text(self.title.clone())),
+            "due" => self
+                .due
+                .as_ref()
+                .map(|due| PropertyValue::Date(due.start.clone())),
+            "done" => Some(PropertyValue::Checkbox(self.done)),
+            "tags" => Some(PropertyValue::Text(self.tags.join(", "))),
+            "priority" => self.priority.clone().map(PropertyValue::Text),
+            _ => self.properties.get(name).cloned(),
+        }
+    }
+
+    /// Converts a page object from a Notion database query
+    pub fn from_notion_page(page: &Value) -> crate::Result<Todo> {
+        Todo::from_notion_page_with(page, &PropertyNames::default())
+    }
+
+    /// Like `from_notion_page`, for databases whose properties are named
+    /// differently
+    pub fn from_notion_page_with(page: &Value, names: &PropertyNames) -> crate::Result<Todo> {
+        let page =
+            Page::deserialize(page).map_err(|e| Error::Schema(format!("invalid page: {e}")))?;
+        Todo::from_page(&page, names)
+    }
+
+    /// Converts a typed page. The title and done checkbox must be there, the
+    /// other fields are left empty when their property is missing or of
+    /// another type.
+    pub fn from_page(page: &Page, names: &PropertyNames) -> crate::Result<Todo> {
+        let properties = &page.properties;
+
+        let title = match properties.get(&names.title) {
+            Some(Property::Title(parts)) if !parts.is_empty() => {
+                parts.iter().map(|part| part.plain_text.as_str()).collect()
+            }
+            property => return Err(Error::Schema(unexpected(&names.title, "title", property))),
+        };
+
+        let statuses = &names.done_when.status;
+        let done = match properties.get(&names.done) {
+            Some(Property::Checkbox(done)) if statuses.is_empty() => *done,
+            Some(Property::Status(option) | Property::Select(option)) if !statuses.is_empty() => {
+                option
+                    .as_ref()
+                    .is_some_and(|option| statuses.contains(&option.name))
+            }
+            property => {
+                let mut error = unexpected(&names.done, names.done_when.kind(), property);
+                if matches!(property, Some(Property::Status(_) | Property::Select(_))) {
+                    error.push_str(
+                        "; list the options meaning done in done_when.status in the config file",
+                    );
+                }
+                return Err(Error::Schema(error));
+            }
+        };
+
+        let due = match properties.get(&names.due) {
+            Some(Property::Date(Some(date))) => Some(DateRange {
+                start: date.start.clone(),
+                end: date.end.clone(),
+            }),
+            _ => None,
+        };
+
+        let tags = match properties.get(&names.tags) {
+            Some(Property::MultiSelect(options)) => {
+                options.iter().map(|option| option.name.clone()).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let priority = match properties.get(&names.priority) {
+            Some(Property::Select(Some(option))) => Some(option.name.clone()),
+            _ => None,
+        };
+
+        let extra = names.extra.iter().filter_map(|name| {
+            let value = PropertyValue::from_notion(properties.get(name)?)?;
+            Some((name.clone(), value))
+        });
+
+        Ok(Todo {
+            id: page.id.clone(),
+            title,
+            due,
+            done,
+            tags,
+            priority,
+            url: page.url.clone(),
+            source: Source::Notion,
+            properties: extra.collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+impl Todo {
+    /// A Notion todo identified by its title, with nothing else set, for
+    /// tests to build on
+    pub(crate) fn sample(title: &str, done: bool, due: Option<&str>) -> Todo {
+        Todo {
+            id: title.to_owned(),
+            title: title.to_owned(),
+            due: due.map(|start| DateRange {
+                start: start.to_owned(),
+                end: None,
+            }),
+            done,
+            tags: Vec::new(),
+            priority: None,
+            url: None,
+            source: Source::Notion,
+            properties: Default::default(),
+        }
+    }
+}
+
+/// Why the property `name` couldn't be read as a `kind`
+fn unexpected(name: &str, kind: &str, property: Option<&Property>) -> String {
+    match property {
+        None => format!("missing '{name}' {kind} property"),
+        Some(Property::Invalid(problem)) => format!("'{name}' {problem}"),
+        Some(property) if property.kind() == kind => format!("'{name}' is empty"),
+        Some(property) => format!(
+            "'{name}' is a {} property, expected {kind}",
+            property.kind()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_notion_page_success() {
+        let page = json!({
+            "id": "page-1",
+            "url": "https://www.notion.so/page-1",
+            "properties": {
+                "Name": {"title": [{"plain_text": "Write report"}]},
+                "Done": {"checkbox": true},
+                "Due": {"date": {"start": "2024-05-01", "end": "2024-05-03"}},
+                "Tags": {"multi_select": [{"name": "work"}, {"name": "writing"}]},
+                "Priority": {"select": {"name": "High"}}
+            }
+        });
+
+        let todo = Todo::from_notion_page(&page).unwrap();
+
+        assert_eq!(
+            todo,
+            Todo {
+                id: "page-1".to_owned(),
+                title: "Write report".to_owned(),
+                due: Some(DateRange {
+                    start: "2024-05-01".to_owned(),
+                    end: Some("2024-05-03".to_owned()),
+                }),
+                done: true,
+                tags: vec!["work".to_owned(), "writing".to_owned()],
+                priority: Some("High".to_owned()),
+                url: Some("https://www.notion.so/page-1".to_owned()),
+                source: Source::Notion,
+                properties: Default::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_notion_page_rich_text_title() {
+        let page = json!({"properties": {
+            "Name": {"type": "title", "title": [
+                {"type": "text", "plain_text": "Review ", "annotations": {"bold": true}},
+                {"type": "mention", "plain_text": "@Jane", "mention": {"type": "user"}},
+                {"type": "text", "plain_text": "'s draft", "href": null}
+            ]},
+            "Done": {"type": "checkbox", "checkbox": false}
+        }});
+
+        let todo = Todo::from_notion_page(&page).unwrap();
+
+        assert_eq!(todo.title, "Review @Jane's draft");
+    }
+
+    #[test]
+    fn test_from_notion_page_optional_properties() {
+        let page = json!({
+            "id": "page-2",
+            "properties": {
+                "Name": {"title": [{"plain_text": "Call mom"}]},
+                "Done": {"checkbox": false},
+                "Due": {"date": null}
+            }
+        });
+
+        let todo = Todo::from_notion_page(&page).unwrap();
+
+        assert_eq!(todo.due, None);
+        assert!(todo.tags.is_empty());
+        assert_eq!(todo.priority, None);
+        assert_eq!(todo.url, None);
+    }
+
+    #[test]
+    fn test_from_notion_page_extra_properties() {
+        let page = json!({
+            "properties": {
+                "Name": {"title": [{"plain_text": "Port to wasm"}]},
+                "Done": {"checkbox": false},
+                "Estimate": {"type": "number", "number": 13},
+                "Status": {"type": "status", "status": {"name": "Doing"}},
+                "Owner": {"type": "people", "people": [
+                    {"object": "user", "id": "user-1", "name": "Ada"},
+                    {"object": "user", "id": "user-2"},
+                    {"object": "user", "id": "user-3", "name": "Grace"}
+                ]},
+                "Labels": {"type": "multi_select", "multi_select": [
+                    {"name": "wasm"}, {"name": "build"}
+                ]},
+                "Files": {"type": "files", "files": []}
+            }
+        });
+        let names = PropertyNames {
+            extra: vec![
+                "Estimate".to_owned(),
+                "Status".to_owned(),
+                "Owner".to_owned(),
+                "Labels".to_owned(),
+                "Files".to_owned(),
+            ],
+            ..PropertyNames::default()
+        };
+
+        let todo = Todo::from_notion_page_with(&page, &names).unwrap();
+
+        assert_eq!(todo.property("Estimate"), Some(PropertyValue::Number(13.0)));
+        assert_eq!(
+            todo.property("Status"),
+            Some(PropertyValue::Text("Doing".to_owned()))
+        );
+        // People without a name, seen without access to users, are left out
+        assert_eq!(
+            todo.property("Owner"),
+            Some(PropertyValue::Text("Ada, Grace".to_owned()))
+        );
+        assert_eq!(
+            todo.property("Labels"),
+            Some(PropertyValue::Text("wasm, build".to_owned()))
+        );
+        assert_eq!(todo.property("Files"), None);
+        assert_eq!(todo.property("done"), Some(PropertyValue::Checkbox(false)));
+        assert_eq!(
+            todo.property("tags"),
+            Some(PropertyValue::Text(String::new()))
+        );
+    }
+
+    #[test]
+    fn test_check_schema() {
+        let names = PropertyNames {
+            extra: vec!["Estimate".to_owned()],
+            ..PropertyNames::default()
+        };
+        let schema = json!({
+            "Name": {"type": "title"},
+            "Done": {"type": "checkbox"},
+            "Due": {"type": "date"},
+            "Estimate": {"type": "number"}
+        });
+        assert!(names.check_schema(&schema).is_empty());
+
+        let schema = json!({
+            "Name": {"type": "title"},
+            "Done": {"type": "select"},
+            "Priority": {"type": "multi_select"}
+        });
+        assert_eq!(
+            names.check_schema(&schema),
+            [
+                "'Done' is a select property, expected checkbox; point properties.done in the config file at a checkbox property",
+                "'Due' is missing; rename the date property or set properties.due in the config file",
+                "'Priority' is a multi_select property, expected select; point properties.priority in the config file at a select property",
+                "'Estimate' is missing; rename the property or drop it from properties.extra",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_schema_done_options() {
+        let names = PropertyNames {
+            done: "Status".to_owned(),
+            done_when: DoneWhen {
+                status: vec!["Complete".to_owned(), "Dropped".to_owned()],
+            },
+            ..PropertyNames::default()
+        };
+        let schema = json!({
+            "Name": {"type": "title"},
+            "Status": {"type": "status", "status": {"options": [
+                {"name": "Not started"},
+                {"name": "Completed"}
+            ]}},
+            "Due": {"type": "date"}
+        });
+
+        assert_eq!(
+            names.check_schema(&schema),
+            [
+                "'Complete' is not an option of 'Status'; did you mean 'Completed' in done_when.status?",
+                "'Dropped' is not an option of 'Status'; list one of Not started, Completed in done_when.status",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_overdue() {
+        use chrono::Utc;
+
+        let now = Utc.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap();
+        let mut todo = Todo::from_notion_page(&json!({
+            "properties": {
+                "Name": {"title": [{"plain_text": "Trip"}]},
+                "Done": {"checkbox": false},
+                "Due": {"date": {"start": "2024-04-30", "end": "2024-05-02"}}
+            }
+        }))
+        .unwrap();
+
+        assert!(!todo.is_overdue(&now));
+        todo.due.as_mut().unwrap().end = Some("2024-05-01".to_owned());
+        assert!(todo.is_overdue(&now));
+        todo.done = true;
+        assert!(!todo.is_overdue(&now));
+    }
+
+    #[test]
+    fn test_from_notion_page_missing_done() {
+        let page = json!({"properties": {"Name": {"title": [{"plain_text": "Call mom"}]}}});
+
+        let result = Todo::from_notion_page(&page);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "missing 'Done' checkbox property"
+        );
+    }
+
+    #[test]
+    fn test_from_notion_page_done_status() {
+        let page = |status: &str| {
+            json!({"properties": {
+                "Name": {"type": "title", "title": [{"plain_text": "Call mom"}]},
+                "Status": {"type": "status", "status": {"name": status}}
+            }})
+        };
+        let names = PropertyNames {
+            done: "Status".to_owned(),
+            done_when: DoneWhen {
+                status: vec!["Done".to_owned(), "Cancelled".to_owned()],
+            },
+            ..PropertyNames::default()
+        };
+
+        let done = |status| {
+            Todo::from_notion_page_with(&page(status), &names)
+                .unwrap()
+                .done
+        };
+        assert!(done("Cancelled"));
+        assert!(!done("In progress"));
+
+        let names = PropertyNames {
+            done: "Status".to_owned(),
+            ..PropertyNames::default()
+        };
+        assert_eq!(
+            Todo::from_notion_page_with(&page("Done"), &names)
+                .unwrap_err()
+                .to_string(),
+            "'Status' is a status property, expected checkbox; list the options meaning done in done_when.status in the config file"
+        );
+    }
+
+    #[test]
+    fn test_from_notion_page_errors() {
+        let error = |properties| {
+            Todo::from_notion_page(&json!({"properties": properties}))
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error(json!({
+                "Name": {"type": "title", "title": [{"plain_text": "Call mom"}]},
+                "Done": {"type": "select", "select": {"name": "Yes"}}
+            })),
+            "'Done' is a select property, expected checkbox; list the options meaning done in done_when.status in the config file"
+        );
+        assert_eq!(
+            error(json!({
+                "Name": {"type": "title", "title": [{"plain_text": "Call mom"}]},
+                "Done": {"type": "checkbox", "checkbox": "yes"}
+            })),
+            "'Done' is not a valid checkbox: invalid type: string \"yes\", expected a boolean"
+        );
+        assert_eq!(
+            error(json!({"Name": {"type": "title", "title": []}})),
+            "'Name' is empty"
+        );
+    }
+
+    #[test]
+    fn test_from_notion_page_with_renamed_properties() {
+        let page = json!({"properties": {
+            "Task": {"title": [{"plain_text": "Call mom"}]},
+            "Completed": {"checkbox": false},
+            "Deadline": {"date": {"start": "2024-05-01"}}
+        }});
+        let names = PropertyNames {
+            title: "Task".to_owned(),
+            done: "Completed".to_owned(),
+            due: "Deadline".to_owned(),
+            ..PropertyNames::default()
+        };
+
+        let todo = Todo::from_notion_page_with(&page, &names).unwrap();
+
+        assert_eq!(todo.title, "Call mom");
+        assert_eq!(todo.due.unwrap().start, "2024-05-01");
+        assert_eq!(
+            Todo::from_notion_page(&page).unwrap_err().to_string(),
+            "missing 'Name' title property"
+        );
+    }
+
+    #[test]
+    fn test_maps_defaults() {
+        let mut names = PropertyNames {
+            extra: vec!["Estimate".to_owned()],
+            ..PropertyNames::default()
+        };
+        assert!(names.maps_defaults());
+
+        names.set("due", "Deadline").unwrap();
+        assert!(!names.maps_defaults());
+    }
+}
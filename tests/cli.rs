@@ -0,0 +1,2356 @@
+//! End-to-end tests running the built binary against a local mock of the
+//! Notion API.
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A request as received by the mock, with header names lowercased
+#[derive(Debug, Clone)]
+struct Recorded {
+    request_line: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Recorded {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Serves the given `(status, body)` responses in order, one per connection
+struct MockNotion {
+    base_url: String,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl MockNotion {
+    fn start(responses: Vec<(u16, String)>) -> MockNotion {
+        MockNotion::serve(responses, String::new())
+    }
+
+    /// Like `start`, with every response carrying the given `Date` header
+    fn start_with_date(responses: Vec<(u16, String)>, date: &str) -> MockNotion {
+        MockNotion::start_with_header(responses, "Date", date)
+    }
+
+    /// Like `start`, with every response carrying the given header
+    fn start_with_header(responses: Vec<(u16, String)>, name: &str, value: &str) -> MockNotion {
+        MockNotion::serve(responses, format!("{name}: {value}\r\n"))
+    }
+
+    fn serve(responses: Vec<(u16, String)>, extra_headers: String) -> MockNotion {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    match line.trim_end().split_once(':') {
+                        Some((key, value)) => {
+                            headers.push((key.to_lowercase(), value.trim().to_owned()))
+                        }
+                        None => break,
+                    }
+                }
+
+                let content_length = headers
+                    .iter()
+                    .find(|(key, _)| key == "content-length")
+                    .map(|(_, value)| value.parse().unwrap())
+                    .unwrap_or(0);
+                let mut req_body = vec![0; content_length];
+                reader.read_exact(&mut req_body).unwrap();
+
+                recorded.lock().unwrap().push(Recorded {
+                    request_line: request_line.trim_end().to_owned(),
+                    headers,
+                    body: String::from_utf8(req_body).unwrap(),
+                });
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{extra_headers}Connection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+
+        MockNotion { base_url, requests }
+    }
+
+    fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn page(title: &str, done: bool, due: Option<&str>) -> serde_json::Value {
+    serde_json::json!({
+        "object": "page",
+        "id": format!("id-{title}"),
+        "properties": {
+            "Name": {"title": [{"plain_text": title}]},
+            "Done": {"checkbox": done},
+            "Due": {"date": due.map(|start| serde_json::json!({"start": start}))}
+        }
+    })
+}
+
+fn tagged(mut page: serde_json::Value, tags: &[&str]) -> serde_json::Value {
+    let options: Vec<_> = tags
+        .iter()
+        .map(|tag| serde_json::json!({"name": tag}))
+        .collect();
+    page["properties"]["Tags"] = serde_json::json!({"multi_select": options});
+    page
+}
+
+fn query_response(pages: Vec<serde_json::Value>) -> String {
+    serde_json::json!({"object": "list", "results": pages, "has_more": false, "next_cursor": null})
+        .to_string()
+}
+
+/// A working directory holding a `.secrets` file pointing at the mock
+fn workdir(mock: &MockNotion) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join(".secrets"),
+        format!(
+            "DB_URL={}/databases/db-1/query\nAPI_KEY=secret-key\n",
+            mock.base_url
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+fn notifieru(dir: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("notifieru").unwrap();
+    cmd.current_dir(dir.path())
+        .env("XDG_CACHE_HOME", dir.path().join("cache"))
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env_remove("NOTIFIERU_DB_URL")
+        .env_remove("NOTIFIERU_API_KEY")
+        .env_remove("NOTIFIERU_AGE_IDENTITY");
+    for proxy in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY", "NO_PROXY"] {
+        cmd.env_remove(proxy).env_remove(proxy.to_lowercase());
+    }
+    cmd
+}
+
+#[test]
+fn lists_todos_from_the_database() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            page("Pay rent", true, Some("2024-05-01")),
+            page("Call mom", false, Some("2024-05-02T09:30:00.000+09:00")),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[x] 0: Pay rent"), "{stdout}");
+    assert!(stdout.contains("| 2024-05-01"), "{stdout}");
+    assert!(stdout.contains("[ ] 1: Call mom"), "{stdout}");
+    assert!(stdout.contains("| 2024-05-02 09:30:00"), "{stdout}");
+    assert!(output.stderr.is_empty());
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].request_line,
+        "POST /v1/databases/db-1/query HTTP/1.1"
+    );
+    assert_eq!(
+        requests[0].header("authorization"),
+        Some("Bearer secret-key")
+    );
+    assert!(requests[0].header("notion-version").is_some());
+    assert!(requests[0]
+        .header("user-agent")
+        .is_some_and(|ua| ua.starts_with("notifieru/")));
+    assert!(requests[0].body.contains("\"sorts\""));
+}
+
+/// A page of results followed by another, continuing at `cursor`
+fn first_page(pages: Vec<serde_json::Value>, cursor: &str) -> String {
+    serde_json::json!({"object": "list", "results": pages, "has_more": true, "next_cursor": cursor})
+        .to_string()
+}
+
+#[test]
+fn follows_pages_of_results() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            first_page(vec![page("Pay rent", false, None)], "cursor-2"),
+        ),
+        (200, query_response(vec![page("Call mom", false, None)])),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("0: Pay rent"), "{stdout}");
+    assert!(stdout.contains("1: Call mom"), "{stdout}");
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].body.contains("start_cursor"));
+    assert!(requests[1].body.contains("\"start_cursor\":\"cursor-2\""));
+    assert!(requests[1].body.contains("\"sorts\""));
+}
+
+#[test]
+fn stops_after_max_pages() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            first_page(vec![page("Pay rent", false, None)], "cursor-2"),
+        ),
+        (
+            200,
+            first_page(vec![page("Call mom", false, None)], "cursor-3"),
+        ),
+    ]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "max_pages = 2\n",
+    )
+    .unwrap();
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1: Call mom"), "{stdout}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("listing only the first 2 pages of todos"),
+        "{stderr}"
+    );
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[test]
+fn reports_invalid_rows_and_keeps_going() {
+    let mut broken = page("Broken", false, None);
+    broken["properties"]["Done"] = serde_json::json!({});
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![broken, page("Fine", false, None)]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.contains("Fine"), "{stdout}");
+    assert!(stderr.contains("todo 0: 'Done' has no type"), "{stderr}");
+}
+
+#[test]
+fn fails_without_secrets_file() {
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir).assert().code(4).get_output().clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("'.secrets' path not found"), "{stderr}");
+}
+
+#[test]
+fn reads_secrets_from_environment() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![page("Pay rent", false, None)]),
+    )]);
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .env(
+            "NOTIFIERU_DB_URL",
+            format!("{}/databases/db-env/query", mock.base_url),
+        )
+        .env("NOTIFIERU_API_KEY", "env-key")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Pay rent"), "{stdout}");
+    let requests = mock.requests();
+    assert_eq!(
+        requests[0].request_line,
+        "POST /v1/databases/db-env/query HTTP/1.1"
+    );
+    assert_eq!(requests[0].header("authorization"), Some("Bearer env-key"));
+}
+
+#[test]
+fn reads_api_key_from_stdin_and_file() {
+    let mock = MockNotion::start(vec![
+        (200, query_response(vec![])),
+        (200, query_response(vec![])),
+    ]);
+    let dir = workdir(&mock);
+    std::fs::write(dir.path().join("token"), "file-key\n").unwrap();
+
+    notifieru(&dir)
+        .arg("--api-key-stdin")
+        .env("NOTIFIERU_API_KEY", "env-key")
+        .write_stdin("stdin-key\n")
+        .assert()
+        .success();
+    notifieru(&dir)
+        .args(["--api-key-file", "token"])
+        .assert()
+        .success();
+
+    let requests = mock.requests();
+    assert_eq!(
+        requests[0].header("authorization"),
+        Some("Bearer stdin-key")
+    );
+    assert_eq!(requests[1].header("authorization"), Some("Bearer file-key"));
+
+    notifieru(&dir)
+        .args(["--api-key-stdin", "--api-key-file", "token"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn explains_error_responses() {
+    let mock = MockNotion::start(vec![(
+        401,
+        r#"{"object": "error", "status": 401, "code": "unauthorized", "message": "API token is invalid."}"#
+            .to_owned(),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir).assert().code(6).get_output().clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("notion returned unauthorized: API token is invalid.; check API_KEY"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn explains_an_unshared_database() {
+    let mock = MockNotion::start(vec![
+        (200, r#"{"object": "user", "name": "Todo bot"}"#.to_owned()),
+        (
+            404,
+            r#"{"object": "error", "status": 404, "code": "object_not_found", "message": "Could not find database with ID: db-1."}"#
+                .to_owned(),
+        ),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .arg("doctor")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("FAIL database: notion returned object_not_found"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("share the database with the integration"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn fails_on_response_over_size_limit() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![page("Pay rent", false, None)]),
+    )]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "max_response_bytes = 64\n",
+    )
+    .unwrap();
+
+    let output = notifieru(&dir).assert().code(5).get_output().clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("response is larger than the maximum of 64 bytes"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn injected_faults_fail_the_run() {
+    let mock = MockNotion::start(vec![]);
+    let dir = workdir(&mock);
+
+    for (fault, message, code) in [
+        ("timeout", "injected fault", 5),
+        ("bad_json", "EOF while parsing", 5),
+        ("rate_limit", "notion returned rate_limited", 6),
+    ] {
+        let output = notifieru(&dir)
+            .args(["--inject-fault", fault])
+            .assert()
+            .code(code)
+            .get_output()
+            .clone();
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains(message), "{fault}: {stderr}");
+    }
+    assert!(mock.requests().is_empty());
+}
+
+#[test]
+fn retries_an_injected_rate_limit() {
+    let mock = MockNotion::start(vec![]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "max_attempts = 5\n",
+    )
+    .unwrap();
+
+    let output = notifieru(&dir)
+        .args(["--inject-fault", "rate_limit"])
+        .assert()
+        .code(6)
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("(injected fault, attempt 5)"), "{stderr}");
+    assert!(mock.requests().is_empty());
+}
+
+#[test]
+fn times_out_on_a_hung_server() {
+    // Connections are queued but never answered
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mock = MockNotion {
+        base_url: format!("http://{}/v1", listener.local_addr().unwrap()),
+        requests: Arc::default(),
+    };
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--timeout", "1s"])
+        .assert()
+        .code(5)
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("request timed out after 1s"), "{stderr}");
+    drop(listener);
+}
+
+#[test]
+fn rejects_unknown_fault() {
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .args(["--inject-fault", "slow"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown fault 'slow'"), "{stderr}");
+}
+
+#[test]
+fn prints_version() {
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .arg("version")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, format!("notifieru {}\n", env!("CARGO_PKG_VERSION")));
+
+    let output = notifieru(&dir)
+        .args(["version", "--verbose"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("git hash:"), "{stdout}");
+    assert!(stdout.contains("supported Notion-Version:"), "{stdout}");
+}
+
+#[test]
+fn version_and_cache_skip_the_config() {
+    let dir = TempDir::new().unwrap();
+    let config_dir = dir.path().join("config/notifieru");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), "not = [valid").unwrap();
+
+    notifieru(&dir).arg("version").assert().success();
+    notifieru(&dir).args(["cache", "status"]).assert().success();
+    notifieru(&dir).arg("doctor").assert().failure();
+}
+
+#[test]
+fn queries_data_source_on_newer_notion_version() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            r#"{"object": "database", "id": "db-1", "data_sources": [{"id": "ds-1", "name": "Todos"}]}"#
+                .to_owned(),
+        ),
+        (200, query_response(vec![page("Pay rent", false, None)])),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--notion-version", "2025-09-03"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Pay rent"), "{stdout}");
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].request_line, "GET /v1/databases/db-1 HTTP/1.1");
+    assert_eq!(
+        requests[1].request_line,
+        "POST /v1/data_sources/ds-1/query HTTP/1.1"
+    );
+    assert_eq!(requests[1].header("notion-version"), Some("2025-09-03"));
+}
+
+#[test]
+fn reads_notion_version_from_config() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            r#"{"object": "database", "id": "db-1", "data_sources": [{"id": "ds-1", "name": "Todos"}]}"#
+                .to_owned(),
+        ),
+        (200, query_response(vec![])),
+        (200, query_response(vec![])),
+    ]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "notion_version = \"2025-09-03\"\n",
+    )
+    .unwrap();
+
+    notifieru(&dir).assert().success();
+    notifieru(&dir)
+        .args(["--notion-version", "2022-06-28"])
+        .assert()
+        .success();
+    let output = notifieru(&dir)
+        .args(["version", "--verbose"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let requests = mock.requests();
+    assert_eq!(requests[1].header("notion-version"), Some("2025-09-03"));
+    assert_eq!(
+        requests[2].request_line,
+        "POST /v1/databases/db-1/query HTTP/1.1"
+    );
+    assert_eq!(requests[2].header("notion-version"), Some("2022-06-28"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("configured Notion-Version:  2025-09-03"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn sends_custom_user_agent_and_headers() {
+    let mock = MockNotion::start(vec![(200, query_response(vec![]))]);
+    let dir = workdir(&mock);
+
+    notifieru(&dir)
+        .args(["--user-agent", "gateway-client/2"])
+        .args(["--header", "X-Tenant: household"])
+        .args(["--header", "X-Trace:abc"])
+        .assert()
+        .success();
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].header("user-agent"), Some("gateway-client/2"));
+    assert_eq!(requests[0].header("x-tenant"), Some("household"));
+    assert_eq!(requests[0].header("x-trace"), Some("abc"));
+}
+
+#[test]
+fn reads_user_agent_and_headers_from_config() {
+    let mock = MockNotion::start(vec![
+        (200, query_response(vec![])),
+        (200, query_response(vec![])),
+    ]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "user_agent = \"gateway-client/2\"\n\n[headers]\nX-Tenant = \"household\"\nX-Trace = \"abc\"\n",
+    )
+    .unwrap();
+
+    notifieru(&dir).assert().success();
+    notifieru(&dir)
+        .args(["--user-agent", "gateway-client/3"])
+        .args(["--header", "x-tenant: office"])
+        .assert()
+        .success();
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].header("user-agent"), Some("gateway-client/2"));
+    assert_eq!(requests[0].header("x-tenant"), Some("household"));
+    assert_eq!(requests[1].header("user-agent"), Some("gateway-client/3"));
+    assert_eq!(requests[1].header("x-tenant"), Some("office"));
+    assert_eq!(requests[1].header("x-trace"), Some("abc"));
+}
+
+#[test]
+fn rejects_malformed_header() {
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .args(["--header", "X-Tenant"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid header 'X-Tenant'"), "{stderr}");
+}
+
+#[test]
+fn leaves_done_todos_out_unless_all() {
+    let mock = MockNotion::start(vec![
+        (200, query_response(vec![page("Call mom", false, None)])),
+        (200, query_response(vec![page("Pay rent", true, None)])),
+    ]);
+    let dir = workdir(&mock);
+
+    notifieru(&dir).assert().success();
+    // Only complete listings are cached
+    assert!(!dir.path().join("cache/notifieru/db-1.json").exists());
+    notifieru(&dir).args(["list", "--all"]).assert().success();
+
+    let requests = mock.requests();
+    let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(
+        body["filter"],
+        serde_json::json!({"property": "Done", "checkbox": {"equals": false}})
+    );
+    let body: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+    assert!(body.get("filter").is_none(), "{body}");
+    assert!(dir.path().join("cache/notifieru/db-1.json").exists());
+}
+
+#[test]
+fn lists_done_todos_last() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            page("Pay rent", true, Some("2024-05-01")),
+            page("Call mom", false, Some("2024-05-02")),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["list", "--show-done", "last"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines[0].starts_with("[ ] 0: Call mom"), "{stdout}");
+    assert!(lines[1].starts_with("[x] 1: Pay rent"), "{stdout}");
+    let body: serde_json::Value = serde_json::from_str(&mock.requests()[0].body).unwrap();
+    assert!(body.get("filter").is_none(), "{body}");
+}
+
+#[test]
+fn lists_todos_done_lately() {
+    let mock = MockNotion::start(vec![(200, query_response(vec![]))]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "[output]\nshow_done = \"7d\"\n",
+    )
+    .unwrap();
+
+    notifieru(&dir).assert().success();
+
+    let body: serde_json::Value = serde_json::from_str(&mock.requests()[0].body).unwrap();
+    let filters = &body["filter"]["or"];
+    assert_eq!(
+        filters[0],
+        serde_json::json!({"property": "Done", "checkbox": {"equals": false}})
+    );
+    assert_eq!(filters[1]["property"], "Completed");
+    let since = filters[1]["date"]["on_or_after"].as_str().unwrap();
+    assert!(
+        chrono::DateTime::parse_from_rfc3339(since).is_ok(),
+        "{since}"
+    );
+}
+
+/// How many compound filters are nested in `filter`, which Notion caps at 2
+fn filter_depth(filter: &serde_json::Value) -> usize {
+    ["and", "or"]
+        .iter()
+        .filter_map(|operator| filter[operator].as_array())
+        .flatten()
+        .map(|filter| filter_depth(filter) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn keeps_filters_for_todos_done_lately_two_levels_deep() {
+    let schema = serde_json::json!({"object": "database", "properties": {
+        "Name": {"type": "title"},
+        "Status": {"type": "status"},
+        "Stage": {"type": "select"}
+    }})
+    .to_string();
+    let mock = MockNotion::start(vec![
+        (200, schema.clone()),
+        (200, query_response(vec![])),
+        (200, schema),
+        (200, query_response(vec![])),
+    ]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "done_when = { status = [\"Done\", \"Cancelled\"] }\n\n[properties]\ndone = \"Status\"\nstatus = \"Stage\"\n\n[output]\nshow_done = \"7d\"\n",
+    )
+    .unwrap();
+
+    notifieru(&dir)
+        .args(["--due-before", "2024-06-01"])
+        .assert()
+        .success();
+    notifieru(&dir)
+        .args(["--status", "Now", "--status", "Next"])
+        .assert()
+        .success();
+
+    let requests = mock.requests();
+    let body: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+    let filter = &body["filter"];
+    assert_eq!(filter_depth(filter), 2, "{filter}");
+    let branches = filter["or"].as_array().unwrap();
+    assert_eq!(branches.len(), 2, "{filter}");
+    assert_eq!(
+        branches[0],
+        serde_json::json!({"and": [
+            {"property": "Due", "date": {"before": "2024-06-01"}},
+            {"property": "Status", "status": {"does_not_equal": "Done"}},
+            {"property": "Status", "status": {"does_not_equal": "Cancelled"}}
+        ]})
+    );
+    assert_eq!(
+        branches[1]["and"][0],
+        serde_json::json!({"property": "Due", "date": {"before": "2024-06-01"}})
+    );
+    assert_eq!(branches[1]["and"][1]["property"], "Completed");
+
+    // Open or done lately, and in either stage, as one branch per combination
+    let body: serde_json::Value = serde_json::from_str(&requests[3].body).unwrap();
+    let filter = &body["filter"];
+    assert_eq!(filter_depth(filter), 2, "{filter}");
+    assert_eq!(filter["or"].as_array().unwrap().len(), 4, "{filter}");
+    assert_eq!(
+        filter["or"][3]["and"][1],
+        serde_json::json!({"property": "Stage", "select": {"equals": "Next"}})
+    );
+}
+
+#[test]
+fn reads_done_from_a_status_property() {
+    // A select works like a status, though Notion filters it as a select
+    for kind in ["status", "select"] {
+        let mock = MockNotion::start(vec![
+            (
+                200,
+                serde_json::json!({"object": "database", "properties": {
+                    "Name": {"type": "title"},
+                    "Status": {"type": kind}
+                }})
+                .to_string(),
+            ),
+            (
+                200,
+                query_response(vec![serde_json::json!({
+                    "id": "page-1",
+                    "properties": {
+                        "Name": {"type": "title", "title": [{"plain_text": "Pay rent"}]},
+                        "Status": {"type": kind, kind: {"name": "Cancelled"}}
+                    }
+                })]),
+            ),
+        ]);
+        let dir = workdir(&mock);
+        std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+        std::fs::write(
+            dir.path().join("config/notifieru/config.toml"),
+            "done_when = { status = [\"Done\", \"Cancelled\"] }\n\n[properties]\ndone = \"Status\"\n",
+        )
+        .unwrap();
+
+        let output = notifieru(&dir).assert().success().get_output().clone();
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("[x] 0: Pay rent"), "{stdout}");
+        let requests = mock.requests();
+        assert_eq!(requests[0].request_line, "GET /v1/databases/db-1 HTTP/1.1");
+        let body: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(
+            body["filter"],
+            serde_json::json!({"and": [
+                {"property": "Status", kind: {"does_not_equal": "Done"}},
+                {"property": "Status", kind: {"does_not_equal": "Cancelled"}}
+            ]})
+        );
+    }
+}
+
+#[test]
+fn filters_by_due_date_in_the_query() {
+    let mock = MockNotion::start(vec![(200, query_response(vec![]))]);
+    let dir = workdir(&mock);
+
+    notifieru(&dir)
+        .args(["--due-after", "2024-05-01", "--due-before", "2024-06-01"])
+        .assert()
+        .success();
+
+    let body: serde_json::Value = serde_json::from_str(&mock.requests()[0].body).unwrap();
+    assert_eq!(
+        body["filter"],
+        serde_json::json!({"and": [
+            {"property": "Done", "checkbox": {"equals": false}},
+            {"property": "Due", "date": {"before": "2024-06-01"}},
+            {"property": "Due", "date": {"after": "2024-05-01"}}
+        ]})
+    );
+
+    notifieru(&dir)
+        .args(["--due-before", "soon"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn prompt_reads_only_the_cache() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            page("Pay rent", false, Some("2024-05-01")),
+            page("Call mom", false, None),
+            page("Buy milk", true, None),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    // Nothing cached yet
+    notifieru(&dir).arg("prompt").assert().success().stdout("");
+
+    notifieru(&dir).arg("--all").assert().success();
+    notifieru(&dir)
+        .arg("prompt")
+        .assert()
+        .success()
+        .stdout("☐2 ⚑1\n");
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn caches_the_last_listing() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![page("Pay rent", false, None)]),
+    )]);
+    let dir = workdir(&mock);
+
+    notifieru(&dir).arg("--all").assert().success();
+    assert!(dir.path().join("cache/notifieru/db-1.json").exists());
+
+    let output = notifieru(&dir)
+        .args(["cache", "status"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("db-1: 1 todos"), "{stdout}");
+
+    notifieru(&dir).args(["cache", "gc"]).assert().success();
+    assert!(dir.path().join("cache/notifieru/db-1.json").exists());
+
+    let output = notifieru(&dir)
+        .args(["cache", "clear"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "removed 1 cached databases\n");
+    assert!(!dir.path().join("cache/notifieru/db-1.json").exists());
+}
+
+#[test]
+fn queries_json_output() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            query_response(vec![
+                page("Pay rent", true, Some("2024-05-01")),
+                page("Call mom", false, Some("2024-05-02")),
+            ]),
+        ),
+        (200, query_response(vec![page("Pay rent", true, None)])),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--query", "todos[?!done].title"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let titles: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(titles, serde_json::json!(["Call mom"]));
+
+    let output = notifieru(&dir)
+        .args(["--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let snapshot: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(snapshot["todos"][0]["title"], "Pay rent");
+}
+
+#[test]
+fn rejects_query_with_table_format() {
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .args(["--format", "table", "--query", "todos"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("--query only applies to --format json"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn filters_by_status_in_the_query() {
+    let schema = |kind: &str| {
+        let options = serde_json::json!([{"name": "In progress"}, {"name": "Blocked"}]);
+        serde_json::json!({"object": "database", "properties": {
+            "Name": {"type": "title"},
+            "Done": {"type": "checkbox"},
+            "Status": {"type": kind, kind: {"options": options}}
+        }})
+        .to_string()
+    };
+    let mock = MockNotion::start(vec![
+        (200, schema("status")),
+        (200, query_response(vec![])),
+        (200, schema("select")),
+        (200, query_response(vec![])),
+        (200, schema("rich_text")),
+        (200, schema("status")),
+    ]);
+    let dir = workdir(&mock);
+
+    notifieru(&dir)
+        .args(["--status", "In progress", "--status", "Blocked"])
+        .assert()
+        .success();
+    notifieru(&dir)
+        .args(["--status", "Blocked"])
+        .assert()
+        .success();
+    let output = notifieru(&dir)
+        .args(["--status", "Blocked"])
+        .assert()
+        .code(7)
+        .get_output()
+        .clone();
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].request_line, "GET /v1/databases/db-1 HTTP/1.1");
+    let body: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+    assert_eq!(
+        body["filter"],
+        serde_json::json!({"and": [
+            {"property": "Done", "checkbox": {"equals": false}},
+            {"or": [
+                {"property": "Status", "status": {"equals": "In progress"}},
+                {"property": "Status", "status": {"equals": "Blocked"}}
+            ]}
+        ]})
+    );
+    let body: serde_json::Value = serde_json::from_str(&requests[3].body).unwrap();
+    assert_eq!(
+        body["filter"]["and"][1],
+        serde_json::json!({"property": "Status", "select": {"equals": "Blocked"}})
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("--status needs 'Status' to be a status or select property"),
+        "{stderr}"
+    );
+
+    // A typo is caught before the query
+    let output = notifieru(&dir)
+        .args(["--status", "Blokced"])
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("'Blokced' is not an option of 'Status', did you mean 'Blocked'?"),
+        "{stderr}"
+    );
+    assert_eq!(mock.requests().len(), 6);
+}
+
+#[test]
+fn filters_by_title_and_tag() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            tagged(page("Pay rent", false, None), &["home"]),
+            tagged(page("Pay invoice", false, None), &["proj-acme"]),
+            tagged(page("Ship release", false, None), &["proj-acme"]),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--match", "^Pay", "--tag", "proj-*"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    assert!(stdout.contains("Pay invoice"), "{stdout}");
+}
+
+#[test]
+fn suggests_a_tag_on_typos() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            tagged(page("Pay rent", false, None), &["home"]),
+            tagged(page("Ship release", false, None), &["work"]),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--tag", "wrk"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("warning: no todo is tagged 'wrk', did you mean 'work'?"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn filters_and_sorts_by_extra_property() {
+    let estimated = |title: &str, estimate: u32| {
+        let mut page = page(title, false, None);
+        page["properties"]["Estimate"] = serde_json::json!({"type": "number", "number": estimate});
+        page
+    };
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            estimated("Fix typo", 1),
+            estimated("Write docs", 3),
+            estimated("Port to wasm", 13),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "[properties]\nextra = [\"Estimate\"]\n",
+    )
+    .unwrap();
+
+    let output = notifieru(&dir)
+        .args(["--filter", "Estimate>=3", "--sort", "Estimate:desc"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].contains("Port to wasm"), "{stdout}");
+    assert!(lines[1].contains("Write docs"), "{stdout}");
+
+    notifieru(&dir)
+        .args(["--filter", "Estimate"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn filters_and_sorts_by_unlisted_property() {
+    let estimated = |title: &str, estimate: u32| {
+        let mut page = page(title, false, None);
+        page["properties"]["Estimate"] = serde_json::json!({"type": "number", "number": estimate});
+        page
+    };
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            estimated("Fix typo", 1),
+            estimated("Port to wasm", 13),
+            estimated("Write docs", 3),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--filter", "Estimate>=3", "--sort", "Estimate"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].contains("Write docs"), "{stdout}");
+    assert!(lines[1].contains("Port to wasm"), "{stdout}");
+}
+
+#[test]
+fn filters_and_sorts_by_tags() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            tagged(page("Pay rent", false, None), &["home"]),
+            tagged(page("Ship release", false, None), &["work"]),
+            tagged(page("Call mom", false, None), &["family"]),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--filter", "tags!=work", "--sort", "tags:desc"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].contains("Pay rent"), "{stdout}");
+    assert!(lines[1].contains("Call mom"), "{stdout}");
+}
+
+#[test]
+fn formats_number_columns_like_notion() {
+    let mut flights = page("Book flights", false, None);
+    flights["properties"]["Budget"] = serde_json::json!({"type": "number", "number": 1234.5});
+    let schema = serde_json::json!({
+        "object": "database",
+        "properties": {
+            "Budget": {"type": "number", "number": {"format": "euro"}}
+        }
+    });
+    let mock = MockNotion::start(vec![
+        (200, query_response(vec![flights])),
+        (200, schema.to_string()),
+    ]);
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "[output]\ncolumns = [\"title\", \"Budget\"]\nlocale = \"de\"\n",
+    )
+    .unwrap();
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("| €1.234,50"), "{stdout}");
+    assert_eq!(
+        mock.requests()[1].request_line,
+        "GET /v1/databases/db-1 HTTP/1.1"
+    );
+}
+
+#[test]
+fn retries_when_rate_limited() {
+    let rate_limited =
+        r#"{"object": "error", "status": 429, "code": "rate_limited", "message": "Slow down."}"#;
+    let mock = MockNotion::start_with_header(
+        vec![
+            (429, rate_limited.to_owned()),
+            (503, "{}".to_owned()),
+            (200, query_response(vec![page("Pay rent", false, None)])),
+        ],
+        "Retry-After",
+        "0",
+    );
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Pay rent"));
+    assert_eq!(mock.requests().len(), 3);
+}
+
+#[test]
+fn gives_up_after_max_attempts() {
+    let rate_limited =
+        r#"{"object": "error", "status": 429, "code": "rate_limited", "message": "Slow down."}"#;
+    let mock = MockNotion::start_with_header(
+        vec![
+            (429, rate_limited.to_owned()),
+            (429, rate_limited.to_owned()),
+        ],
+        "Retry-After",
+        "0",
+    );
+    let dir = workdir(&mock);
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        "max_attempts = 2\n",
+    )
+    .unwrap();
+
+    notifieru(&dir).assert().code(6);
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[test]
+fn connects_through_a_proxy() {
+    let proxy = MockNotion::start(vec![(403, String::new())]);
+    let notion = MockNotion::start(vec![]);
+    let dir = workdir(&notion);
+
+    notifieru(&dir)
+        .env(
+            "HTTP_PROXY",
+            &proxy.base_url[..proxy.base_url.len() - "/v1".len()],
+        )
+        .assert()
+        .failure();
+
+    let requests = proxy.requests();
+    let notion_host = notion
+        .base_url
+        .trim_start_matches("http://")
+        .trim_end_matches("/v1");
+    assert_eq!(
+        requests[0].request_line,
+        format!("CONNECT {notion_host} HTTP/1.1")
+    );
+    assert!(notion.requests().is_empty());
+}
+
+#[test]
+fn skips_the_proxy_for_no_proxy_hosts() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![page("Pay rent", false, None)]),
+    )]);
+    let dir = workdir(&mock);
+
+    notifieru(&dir)
+        .env("http_proxy", "http://127.0.0.1:1")
+        .env("NO_PROXY", "localhost,127.0.0.1")
+        .assert()
+        .success();
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn shows_extra_properties_as_columns() {
+    let mut flights = page("Book flights", false, Some("2024-05-01"));
+    flights["properties"]["Assignee"] =
+        serde_json::json!({"people": [{"object": "user", "id": "user-1", "name": "Ada"}]});
+    flights["properties"]["Labels"] =
+        serde_json::json!({"multi_select": [{"name": "travel"}, {"name": "family"}]});
+    let mock = MockNotion::start(vec![(200, query_response(vec![flights]))]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["list", "--show-property", "Assignee"])
+        .args(["--show-property", "Labels"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("[ ] 0: Book flights"), "{stdout}");
+    assert!(
+        stdout.contains("| 2024-05-01 | Ada | travel, family\n"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn diffs_against_previous_listing_and_snapshot() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            query_response(vec![
+                page("Pay rent", false, Some("2024-05-01")),
+                page("Call mom", false, None),
+            ]),
+        ),
+        (
+            200,
+            query_response(vec![
+                page("Pay rent", true, Some("2024-05-03")),
+                page("Ship release", false, None),
+            ]),
+        ),
+        (200, query_response(vec![])),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .arg("diff")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("no cached listing"), "{stderr}");
+
+    notifieru(&dir).arg("--all").assert().success();
+    std::fs::copy(
+        dir.path().join("cache/notifieru/db-1.json"),
+        dir.path().join("before.json"),
+    )
+    .unwrap();
+
+    let output = notifieru(&dir)
+        .arg("diff")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("added:       Ship release"), "{stdout}");
+    assert!(stdout.contains("removed:     Call mom"), "{stdout}");
+    assert!(stdout.contains("completed:   Pay rent"), "{stdout}");
+    assert!(
+        stdout.contains(
+            "rescheduled: Pay rent                            | 2024-05-01 -> 2024-05-03"
+        ),
+        "{stdout}"
+    );
+
+    let output = notifieru(&dir)
+        .args(["diff", "--against", "before.json"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("removed:     Pay rent"), "{stdout}");
+    assert!(stdout.contains("removed:     Call mom"), "{stdout}");
+}
+
+#[test]
+fn publishes_stats_to_a_page() {
+    let old_stats = serde_json::json!({
+        "object": "list",
+        "results": [
+            {"id": "block-keep", "type": "paragraph", "paragraph": {"rich_text": []}},
+            {
+                "id": "block-old",
+                "type": "callout",
+                "callout": {"rich_text": [{"plain_text": "notifieru stats · updated yesterday"}]}
+            },
+            {
+                "id": "block-older",
+                "type": "callout",
+                "callout": {"rich_text": [{"plain_text": "notifieru stats · updated last week"}]}
+            }
+        ],
+        "has_more": false,
+        "next_cursor": null
+    });
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            query_response(vec![
+                page("Pay rent", true, Some("2024-05-01")),
+                page("Call mom", false, None),
+            ]),
+        ),
+        (200, old_stats.to_string()),
+        (200, r#"{"object": "block"}"#.to_owned()),
+        (200, r#"{"object": "block"}"#.to_owned()),
+        (200, query_response(vec![])),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["stats", "--publish", "page-9"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("2 todos: 1 done, 1 open"), "{stdout}");
+
+    let requests = mock.requests();
+    assert_eq!(
+        requests[1].request_line,
+        "GET /v1/blocks/page-9/children HTTP/1.1"
+    );
+    // Deleted concurrently, in either order
+    let mut deleted: Vec<_> = requests[2..4]
+        .iter()
+        .map(|request| request.request_line.as_str())
+        .collect();
+    deleted.sort();
+    assert_eq!(
+        deleted,
+        [
+            "DELETE /v1/blocks/block-old HTTP/1.1",
+            "DELETE /v1/blocks/block-older HTTP/1.1"
+        ]
+    );
+    assert_eq!(
+        requests[4].request_line,
+        "PATCH /v1/blocks/page-9/children HTTP/1.1"
+    );
+    assert!(requests[4].body.contains("notifieru stats"));
+}
+
+#[test]
+fn gha_format_annotates_and_gates_on_thresholds() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            query_response(vec![
+                page("Fix login crash", false, Some("2020-01-01")),
+                page("Update changelog", false, None),
+            ]),
+        ),
+        (200, query_response(vec![page("Done long ago", true, None)])),
+    ]);
+    let dir = workdir(&mock);
+    let summary = dir.path().join("summary.md");
+
+    let output = notifieru(&dir)
+        .args(["--format", "gha", "--max-overdue", "0"])
+        .env("GITHUB_STEP_SUMMARY", &summary)
+        .assert()
+        .code(2)
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stdout,
+        "::warning title=Overdue todo::Fix login crash (due 2020-01-01)\n"
+    );
+    assert!(
+        stderr.contains("1 overdue todos exceed the maximum of 0"),
+        "{stderr}"
+    );
+    let summary = std::fs::read_to_string(summary).unwrap();
+    assert!(
+        summary.contains("| ⚠️ overdue | Fix login crash | 2020-01-01 |"),
+        "{summary}"
+    );
+
+    notifieru(&dir).args(["--max-open", "0"]).assert().success();
+}
+
+#[test]
+fn list_subcommand_reads_secrets_from_given_path() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![page("Pay rent", false, None)]),
+    )]);
+    let secrets = workdir(&mock);
+    let dir = TempDir::new().unwrap();
+    let secrets_path = secrets.path().join(".secrets");
+
+    let output = notifieru(&dir)
+        .arg("list")
+        .arg("--secrets-path")
+        .arg(&secrets_path)
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[ ] 0: Pay rent"), "{stdout}");
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[test]
+fn prints_config_with_masked_api_key() {
+    let mock = MockNotion::start(vec![]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["config", "--notion-version", "2025-09-03"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("database: db-1"), "{stdout}");
+    assert!(stdout.contains("api key: secr****"), "{stdout}");
+    assert!(!stdout.contains("secret-key"), "{stdout}");
+    assert!(stdout.contains("notion version: 2025-09-03"), "{stdout}");
+    assert!(mock.requests().is_empty());
+}
+
+#[test]
+fn doctor_reports_each_check() {
+    let mock = MockNotion::start(vec![
+        (200, r#"{"object": "user", "name": "Todo bot"}"#.to_owned()),
+        (200, query_response(vec![])),
+        (
+            200,
+            serde_json::json!({"object": "database", "properties": {
+                "Name": {"type": "title"},
+                "Done": {"type": "checkbox"},
+                "Due": {"type": "date"}
+            }})
+            .to_string(),
+        ),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .arg("doctor")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("ok   secrets"), "{stdout}");
+    assert!(
+        stdout.contains("ok   api key: authenticated as Todo bot"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("ok   database: db-1 is readable"),
+        "{stdout}"
+    );
+
+    assert!(
+        stdout.contains("ok   schema: 'Name', 'Done' and 'Due' found"),
+        "{stdout}"
+    );
+
+    let requests = mock.requests();
+    assert_eq!(requests[0].request_line, "GET /v1/users/me HTTP/1.1");
+    assert!(requests[1].body.contains("\"page_size\":1"));
+    assert_eq!(requests[2].request_line, "GET /v1/databases/db-1 HTTP/1.1");
+}
+
+#[test]
+fn doctor_reports_schema_mismatches() {
+    let mock = MockNotion::start(vec![
+        (200, r#"{"object": "user", "name": "Todo bot"}"#.to_owned()),
+        (200, query_response(vec![])),
+        (
+            200,
+            serde_json::json!({"object": "database", "properties": {
+                "Name": {"type": "title"},
+                "Done": {"type": "checkbox"},
+                "Deadline": {"type": "date"}
+            }})
+            .to_string(),
+        ),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .arg("doctor")
+        .assert()
+        .code(7)
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains(
+            "FAIL schema: 'Due' is missing; rename the date property or set properties.due"
+        ),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn doctor_fails_on_rejected_api_key() {
+    let mock = MockNotion::start(vec![
+        (
+            401,
+            r#"{"object": "error", "status": 401, "code": "unauthorized", "message": "API token is invalid."}"#
+                .to_owned(),
+        ),
+        (401, r#"{"object": "error", "status": 401}"#.to_owned()),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .arg("doctor")
+        .assert()
+        .code(1)
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stdout.contains("FAIL api key: notion returned unauthorized: API token is invalid."),
+        "{stdout}"
+    );
+    assert!(stdout.contains("FAIL database"), "{stdout}");
+    assert!(stderr.contains("2 checks failed"), "{stderr}");
+}
+
+#[test]
+fn notify_dry_run_lists_due_todos() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            page("Pay rent", false, Some("2000-01-01")),
+            page("Renew passport", false, Some("2999-01-01")),
+            page("Call mom", true, Some("2000-01-01")),
+            page("Someday", false, None),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["notify", "--dry-run", "--within", "2d"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "Overdue: Pay rent (2000-01-01)\n");
+}
+
+/// The `event` key of every line of an `--events` stream
+fn event_names(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .map(|line| {
+            let event: serde_json::Value = serde_json::from_str(line).unwrap();
+            event["event"].as_str().unwrap().to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn streams_skipped_todos_as_warnings() {
+    let mut broken = page("Broken", false, None);
+    broken["properties"]["Done"] = serde_json::json!({});
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![broken, page("Fine", false, None)]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["list", "--events"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        event_names(&stdout),
+        ["fetch_started", "warning", "fetch_completed", "todo"]
+    );
+    assert!(
+        stdout.contains("\"message\":\"skipping todo 0: 'Done' has no type\""),
+        "{stdout}"
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn streams_events() {
+    let mock = MockNotion::start(vec![
+        (
+            200,
+            query_response(vec![
+                page("Pay rent", false, Some("2000-01-01")),
+                page("Call mom", false, None),
+            ]),
+        ),
+        (
+            200,
+            query_response(vec![page("Pay rent", false, Some("2000-01-01"))]),
+        ),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["list", "--events"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        event_names(&stdout),
+        ["fetch_started", "fetch_completed", "todo", "todo"]
+    );
+    assert!(stdout.contains("\"title\":\"Call mom\""), "{stdout}");
+
+    let output = notifieru(&dir)
+        .args(["notify", "--dry-run", "--events"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        event_names(&stdout),
+        ["fetch_started", "fetch_completed", "notification_sent"]
+    );
+    let sent: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(sent["body"], "Pay rent (2000-01-01)");
+    assert_eq!(sent["dry_run"], true);
+
+    notifieru(&dir)
+        .args(["diff", "--events"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn usage_errors_exit_with_one() {
+    let dir = TempDir::new().unwrap();
+
+    notifieru(&dir).args(["list", "--nope"]).assert().code(1);
+    notifieru(&dir).arg("--help").assert().success();
+}
+
+#[test]
+fn notify_alerts_on_breached_thresholds() {
+    let todos = query_response(vec![
+        page("Pay rent", false, Some("2000-01-01")),
+        page("Call mom", false, None),
+    ]);
+    let mock = MockNotion::start(vec![(200, todos.clone()), (200, todos)]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["notify", "--dry-run", "--max-overdue-age", "72h"])
+        .assert()
+        .code(2)
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.starts_with("Overdue: Pay rent"), "{stdout}");
+    assert!(
+        stdout.contains("notifieru alert: 'Pay rent' is overdue by"),
+        "{stdout}"
+    );
+    assert!(stderr.contains("threshold exceeded"), "{stderr}");
+
+    notifieru(&dir)
+        .args(["notify", "--dry-run", "--max-open", "2"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn reads_settings_from_config_file() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![serde_json::json!({
+            "id": "page-1",
+            "properties": {
+                "Task": {"title": [{"plain_text": "Pay rent"}]},
+                "Completed": {"checkbox": true},
+                "Deadline": {"date": {"start": "2024-05-01"}}
+            }
+        })]),
+    )]);
+    let dir = TempDir::new().unwrap();
+    let secrets_path = dir.path().join("credentials");
+    std::fs::write(&secrets_path, "API_KEY=secret-key\n").unwrap();
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        format!(
+            r#"
+database_url = "{}/databases/db-conf/query"
+secrets_path = "{}"
+
+[properties]
+title = "Task"
+done = "Completed"
+due = "Deadline"
+
+[sort]
+direction = "descending"
+"#,
+            mock.base_url,
+            secrets_path.display()
+        ),
+    )
+    .unwrap();
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[x] 0: Pay rent"), "{stdout}");
+    assert!(stdout.contains("| 2024-05-01"), "{stdout}");
+    let requests = mock.requests();
+    assert_eq!(
+        requests[0].request_line,
+        "POST /v1/databases/db-conf/query HTTP/1.1"
+    );
+    assert!(
+        requests[0]
+            .body
+            .contains(r#"{"direction":"descending","property":"Deadline"}"#),
+        "{}",
+        requests[0].body
+    );
+}
+
+#[test]
+fn maps_properties_from_flags() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![serde_json::json!({
+            "id": "page-1",
+            "properties": {
+                "Task": {"title": [{"plain_text": "Pay rent"}]},
+                "Completed": {"checkbox": true},
+                "Deadline": {"date": {"start": "2024-05-01"}}
+            }
+        })]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--property", "title=Task", "--property", "done=Completed"])
+        .args(["--property", "due=Deadline"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[x] 0: Pay rent"), "{stdout}");
+    assert!(stdout.contains("| 2024-05-01"), "{stdout}");
+    assert!(
+        mock.requests()[0].body.contains(r#""property":"Deadline""#),
+        "{}",
+        mock.requests()[0].body
+    );
+}
+
+#[test]
+fn discovers_differently_named_properties() {
+    let mock = MockNotion::start(vec![
+        (
+            400,
+            r#"{"object": "error", "status": 400, "code": "validation_error", "message": "Could not find sort property with name or id: Due"}"#
+                .to_owned(),
+        ),
+        (
+            200,
+            serde_json::json!({"object": "database", "properties": {
+                "Task": {"type": "title"},
+                "Completed": {"type": "checkbox"},
+                "Deadline": {"type": "date"}
+            }})
+            .to_string(),
+        ),
+        (
+            200,
+            query_response(vec![serde_json::json!({
+                "id": "page-1",
+                "properties": {
+                    "Task": {"title": [{"plain_text": "Pay rent"}]},
+                    "Completed": {"checkbox": true},
+                    "Deadline": {"date": {"start": "2024-05-01"}}
+                }
+            })]),
+        ),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stdout.contains("[x] 0: Pay rent"), "{stdout}");
+    assert!(
+        stderr.contains("reading 'Task', 'Deadline' and 'Completed'"),
+        "{stderr}"
+    );
+    let requests = mock.requests();
+    assert_eq!(requests[1].request_line, "GET /v1/databases/db-1 HTTP/1.1");
+    assert!(
+        requests[2].body.contains(r#""property":"Deadline""#),
+        "{}",
+        requests[2].body
+    );
+}
+
+#[test]
+fn discovers_properties_when_sorting_by_an_extra_one() {
+    let task = |title: &str, estimate: u32| {
+        serde_json::json!({
+            "id": format!("id-{title}"),
+            "properties": {
+                "Task": {"title": [{"plain_text": title}]},
+                "Completed": {"checkbox": false},
+                "Deadline": {"date": null},
+                "Estimate": {"type": "number", "number": estimate}
+            }
+        })
+    };
+    let mock = MockNotion::start(vec![
+        (
+            400,
+            r#"{"object": "error", "status": 400, "code": "validation_error", "message": "Could not find sort property with name or id: Due"}"#
+                .to_owned(),
+        ),
+        (
+            200,
+            serde_json::json!({"object": "database", "properties": {
+                "Task": {"type": "title"},
+                "Completed": {"type": "checkbox"},
+                "Deadline": {"type": "date"},
+                "Estimate": {"type": "number"}
+            }})
+            .to_string(),
+        ),
+        (
+            200,
+            query_response(vec![task("Fix typo", 1), task("Port to wasm", 13)]),
+        ),
+    ]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args(["--sort", "Estimate:desc"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].contains("Port to wasm"), "{stdout}");
+    assert!(lines[1].contains("Fix typo"), "{stdout}");
+    assert_eq!(
+        mock.requests()[1].request_line,
+        "GET /v1/databases/db-1 HTTP/1.1"
+    );
+}
+
+#[test]
+fn selects_profile() {
+    let mock = MockNotion::start(vec![
+        (200, query_response(vec![page("Pay rent", false, None)])),
+        (200, query_response(vec![page("Ship release", false, None)])),
+    ]);
+    let dir = TempDir::new().unwrap();
+    for name in ["personal", "work"] {
+        std::fs::write(
+            dir.path().join(format!("{name}.secrets")),
+            format!(
+                "DB_URL={}/databases/db-{name}/query\nAPI_KEY={name}-key\n",
+                mock.base_url
+            ),
+        )
+        .unwrap();
+    }
+    std::fs::create_dir_all(dir.path().join("config/notifieru")).unwrap();
+    std::fs::write(
+        dir.path().join("config/notifieru/config.toml"),
+        r#"
+default_profile = "personal"
+
+[profiles.personal]
+secrets_path = "personal.secrets"
+
+[profiles.work]
+secrets_path = "work.secrets"
+"#,
+    )
+    .unwrap();
+
+    notifieru(&dir).assert().success();
+    notifieru(&dir)
+        .args(["--profile", "work"])
+        .assert()
+        .success();
+
+    let requests = mock.requests();
+    assert_eq!(
+        requests[0].request_line,
+        "POST /v1/databases/db-personal/query HTTP/1.1"
+    );
+    assert_eq!(
+        requests[1].request_line,
+        "POST /v1/databases/db-work/query HTTP/1.1"
+    );
+
+    let output = notifieru(&dir)
+        .args(["--profile", "home"])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown profile 'home'"), "{stderr}");
+}
+
+#[test]
+fn init_writes_secrets_and_config() {
+    let search = serde_json::json!({
+        "object": "list",
+        "results": [
+            {"object": "database", "id": "db-reading", "title": [{"plain_text": "Reading list"}]},
+            {"object": "database", "id": "db-todos", "title": [{"plain_text": "Todos"}]}
+        ],
+        "has_more": false,
+        "next_cursor": null
+    });
+    let mock = MockNotion::start(vec![
+        (200, search.to_string()),
+        (200, query_response(vec![])),
+        (
+            200,
+            serde_json::json!({"object": "database", "properties": {
+                "Name": {"type": "title"},
+                "Done": {"type": "checkbox"},
+                "Due": {"type": "date"}
+            }})
+            .to_string(),
+        ),
+        (200, query_response(vec![page("Pay rent", false, None)])),
+    ]);
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .args(["init", "--api-base-url", &mock.base_url])
+        .write_stdin("secret-key\n2\n")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("  2. Todos (db-todos)"), "{stdout}");
+    let secrets = std::fs::read_to_string(dir.path().join(".secrets")).unwrap();
+    assert_eq!(
+        secrets,
+        format!(
+            "DB_URL={}/databases/db-todos/query\nAPI_KEY=secret-key\n",
+            mock.base_url
+        )
+    );
+    let config = std::fs::read_to_string(dir.path().join("config/notifieru/config.toml")).unwrap();
+    assert!(config.starts_with("secrets_path = "), "{config}");
+    let requests = mock.requests();
+    assert_eq!(requests[0].request_line, "POST /v1/search HTTP/1.1");
+    assert_eq!(
+        requests[1].request_line,
+        "POST /v1/databases/db-todos/query HTTP/1.1"
+    );
+
+    // The written files work, and aren't overwritten by accident
+    notifieru(&dir).assert().success();
+    let output = notifieru(&dir)
+        .arg("init")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("pass --force to overwrite it"), "{stderr}");
+}
+
+#[test]
+fn init_offers_status_options_meaning_done() {
+    let schema = serde_json::json!({"object": "database", "properties": {
+        "Name": {"type": "title"},
+        "Due": {"type": "date"},
+        "Status": {"type": "status", "status": {"options": [
+            {"name": "Not started"},
+            {"name": "In progress"},
+            {"name": "Done"},
+            {"name": "Archived"}
+        ]}}
+    }})
+    .to_string();
+    let search = serde_json::json!({
+        "object": "list",
+        "results": [{"object": "database", "id": "db-todos", "title": [{"plain_text": "Todos"}]}],
+        "has_more": false,
+        "next_cursor": null
+    })
+    .to_string();
+    let mock = MockNotion::start(vec![
+        (200, search.clone()),
+        (200, query_response(vec![])),
+        (200, schema.clone()),
+        (200, search),
+        (200, query_response(vec![])),
+        (200, schema.clone()),
+        (200, schema),
+        (
+            200,
+            query_response(vec![serde_json::json!({
+                "id": "page-1",
+                "properties": {
+                    "Name": {"type": "title", "title": [{"plain_text": "Pay rent"}]},
+                    "Status": {"type": "status", "status": {"name": "Archived"}}
+                }
+            })]),
+        ),
+    ]);
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .args(["init", "--api-base-url", &mock.base_url])
+        .write_stdin("secret-key\n1\n3, Archivd\n")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("'Archivd' is not an option of 'Status', did you mean 'Archived'?"),
+        "{stderr}"
+    );
+
+    let output = notifieru(&dir)
+        .args(["init", "--api-base-url", &mock.base_url])
+        .write_stdin("secret-key\n1\n3, Archived\n")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("  4. Archived"), "{stdout}");
+    let config = std::fs::read_to_string(dir.path().join("config/notifieru/config.toml")).unwrap();
+    assert!(
+        config.contains(
+            "done_when = { status = [\"Done\",\"Archived\"] }\n\n[properties]\ndone = \"Status\"\n"
+        ),
+        "{config}"
+    );
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[x] 0: Pay rent"), "{stdout}");
+}
+
+#[test]
+fn rejects_invalid_config_file() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("custom.toml");
+    std::fs::write(&config, "[output]\nformat = \"xml\"\n").unwrap();
+
+    let output = notifieru(&dir)
+        .arg("--config")
+        .arg(&config)
+        .assert()
+        .code(3)
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("custom.toml"), "{stderr}");
+    assert!(stderr.contains("xml"), "{stderr}");
+}
+
+#[test]
+fn warns_about_clock_skew() {
+    let mock = MockNotion::start_with_date(
+        vec![(200, query_response(vec![page("Pay rent", false, None)]))],
+        "Sat, 01 Jan 2000 00:00:00 GMT",
+    );
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir).assert().success().get_output().clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("warning: the local clock is") && stderr.contains("ahead of Notion's"),
+        "{stderr}"
+    );
+}
+
+#[cfg(not(feature = "keyring"))]
+#[test]
+fn set_key_needs_keyring_feature() {
+    let dir = TempDir::new().unwrap();
+
+    let output = notifieru(&dir)
+        .args(["config", "set-key"])
+        .write_stdin("secret-key\n")
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("rebuild with the keyring feature"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn exports_several_formats_from_one_fetch() {
+    let mock = MockNotion::start(vec![(
+        200,
+        query_response(vec![
+            page("Pay rent", false, Some("2000-01-01")),
+            page("Call mom", true, None),
+        ]),
+    )]);
+    let dir = workdir(&mock);
+
+    let output = notifieru(&dir)
+        .args([
+            "--format",
+            "table",
+            "--export",
+            "json:todos.json",
+            "--export",
+            "gha:annotations.txt",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[ ] 0: Pay rent"), "{stdout}");
+    let json = std::fs::read_to_string(dir.path().join("todos.json")).unwrap();
+    let snapshot: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot["todos"][1]["title"], "Call mom");
+    let annotations = std::fs::read_to_string(dir.path().join("annotations.txt")).unwrap();
+    assert_eq!(
+        annotations,
+        "::warning title=Overdue todo::Pay rent (due 2000-01-01)\n"
+    );
+    assert_eq!(mock.requests().len(), 1);
+}
@@ -0,0 +1,136 @@
+//! Golden-file tests for every output format, rendered from one shared fixture.
+//!
+//! Run `cargo insta review` (or `INSTA_UPDATE=always cargo test`) after an
+//! intentional formatting change and commit the updated snapshots.
+
+use notifieru::diff::Diff;
+use notifieru::render;
+use notifieru::review::{Period, Review};
+use notifieru::snapshot::Snapshot;
+
+/// The moment golden tests treat as now, a day after the fixture was taken
+fn now() -> chrono::DateTime<chrono::Utc> {
+    use chrono::TimeZone;
+    chrono::Utc.with_ymd_and_hms(2024, 5, 3, 12, 0, 0).unwrap()
+}
+
+fn fixture() -> Snapshot {
+    Snapshot::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/todos.json"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn golden_table() {
+    insta::assert_snapshot!(render::table(&fixture().todos));
+}
+
+#[test]
+fn golden_table_truncated() {
+    let options = render::TableOptions {
+        title_width: Some(20),
+        max_rows: Some(3),
+        ..render::TableOptions::default()
+    };
+    insta::assert_snapshot!(render::table_with(&fixture().todos, &options));
+}
+
+#[test]
+fn golden_table_columns() {
+    let options = render::TableOptions {
+        columns: ["priority", "title", "tags", "due"]
+            .map(str::to_owned)
+            .to_vec(),
+        ..render::TableOptions::default()
+    };
+    insta::assert_snapshot!(render::table_with(&fixture().todos, &options));
+}
+
+#[test]
+fn golden_table_wrapped() {
+    let options = render::TableOptions {
+        title_width: Some(20),
+        wrap: true,
+        ..render::TableOptions::default()
+    };
+    insta::assert_snapshot!(render::table_with(&fixture().todos, &options));
+}
+
+#[test]
+fn golden_json() {
+    insta::assert_snapshot!(render::json(&fixture()).unwrap());
+}
+
+#[test]
+fn golden_json_query() {
+    insta::assert_snapshot!(render::json_query(
+        &fixture(),
+        "todos[?!done].{title: title, due: due.start}"
+    )
+    .unwrap());
+}
+
+#[test]
+fn golden_diff() {
+    let before = fixture().todos;
+    let mut after = before.clone();
+    after[1].done = true;
+    after[2].due.as_mut().unwrap().end = Some("2024-05-13".to_owned());
+    after.remove(0);
+
+    insta::assert_snapshot!(render::diff(&Diff::between(&before, &after)));
+}
+
+#[test]
+fn golden_review_markdown() {
+    let review = Review::new(&fixture().todos, Period::Week, &now());
+
+    insta::assert_snapshot!(render::review_markdown(&review));
+}
+
+#[test]
+fn golden_gha() {
+    let todos = fixture().todos;
+
+    insta::assert_snapshot!(format!(
+        "{}\n{}",
+        render::gha_annotations(&todos, &now()),
+        render::gha_summary(&todos, &now())
+    ));
+}
+
+#[test]
+fn golden_quickfix() {
+    insta::assert_snapshot!(render::quickfix(&fixture().todos, &now()));
+}
+
+#[test]
+fn golden_org() {
+    insta::assert_snapshot!(render::org(&fixture().todos));
+}
+
+#[test]
+fn golden_widget() {
+    let options = render::TableOptions {
+        max_rows: Some(6),
+        ..render::TableOptions::default()
+    };
+    let widget = render::widget(&fixture().todos, &options, &now());
+
+    // Snapshots trim trailing whitespace, so the padding is checked here
+    let lines: Vec<_> = widget.lines().map(|line| line.chars().count()).collect();
+    assert_eq!(lines, [49; 7]);
+    insta::assert_snapshot!(widget);
+}
+
+#[test]
+fn golden_alfred() {
+    insta::assert_snapshot!(render::alfred(&fixture().todos, &now()).unwrap());
+}
+
+#[test]
+fn golden_prompt() {
+    insta::assert_snapshot!(render::prompt(&fixture().todos, &now()));
+}